@@ -0,0 +1,353 @@
+// transfer autopilot: flies the burns in the planner. it points the rocket along each
+// burn, lights the engine so that the burn is centered on its node, throttles down for
+// the last fraction of a step and cuts off once the delta-v is in. following a transfer
+// from the porkchop plot it also plans the mid-course correction after leaving the
+// launch planet, a trim of the closest approach on arrival and then either a capture
+// burn or an aerocapture pass. any manual input hands control back.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::hud::format_duration;
+use crate::physics::{
+    aerocapture_periapsis, approach, capture_burn, coast, impact_offset, is_stable, lambert, periapsis_raise,
+    periapsis_trim, Maneuver, Propulsion, System, Vec2, G,
+};
+use crate::planner::Planner;
+use crate::rockets::{Attitude, FlightStatus, RocketInstance};
+
+// m/s left in a burn that counts as done
+const CUTOFF: f64 = 0.05;
+
+// the mid-course correction goes this long after leaving the launch planet's sphere of
+// influence, clear of its pull
+const CORRECTION_DELAY: f64 = 86400.0;
+
+// height above the target planet's surface the approach is aimed at and captured at
+pub const CAPTURE_ALTITUDE: f64 = 300_000.0;
+
+// the closest approach is trimmed this long after entering the target's sphere of influence
+const TRIM_DELAY: f64 = 600.0;
+
+// height of the apoapsis an aerocapture pass aims to leave, the periapsis is then raised
+// out of the air to `CAPTURE_ALTITUDE`
+const AEROCAPTURE_APOAPSIS: f64 = 30_000_000.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum Leg {
+    #[default]
+    Departure, // parked at the launch planet, departure burn planned
+    Cruise,      // around the star, correction planned
+    Approach,    // inside the target's sphere of influence, closest approach trim planned
+    Aerocapture, // falling into the target's air to be slowed down by it
+    Capture,     // capture burn or periapsis raise planned
+    Arrived,     // in a stable orbit around the target
+}
+
+impl Leg {
+    pub fn name(self) -> &'static str {
+        match self {
+            Leg::Departure => "departure",
+            Leg::Cruise => "cruise",
+            Leg::Approach => "approach",
+            Leg::Aerocapture => "aerocapture",
+            Leg::Capture => "capture",
+            Leg::Arrived => "arrived",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Autopilot {
+    pub engaged: bool,
+    pub aerocapture: bool, // arrive by a pass through the target's air instead of a capture burn
+    pub leg: Leg,
+    arrival: Option<f64>,      // simulation time the followed transfer reaches the target
+    burn: Option<(f64, Vec2)>, // node time and world delta-v still to give of the burn under way
+}
+
+impl Autopilot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // follow the transfer arriving at `arrival`, starting with its departure burn
+    pub fn follow(&mut self, arrival: f64) {
+        self.arrival = Some(arrival);
+        self.leg = Leg::Departure;
+    }
+
+    pub fn engage(&mut self) {
+        self.engaged = true;
+    }
+
+    // hand control back; a burn under way stops being tracked, the engine is left alone
+    pub fn disengage(&mut self) {
+        self.engaged = false;
+        self.burn = None;
+    }
+
+    // fly the coming step of `dt` seconds: plan the next leg once the rocket changes
+    // sphere of influence, start the next burn when it is due and steer and throttle the
+    // one under way. returns what is worth telling the player.
+    pub fn fly(
+        &mut self,
+        rocket: &mut RocketInstance,
+        planner: &mut Planner,
+        system: &System,
+        sun: usize,
+        target: Option<usize>,
+        dt: f64,
+    ) -> Option<String> {
+        if !self.engaged || rocket.status != FlightStatus::Flying {
+            return None;
+        }
+        let message = self.plan_leg(rocket, planner, system, sun, target);
+
+        let Some((node, remaining)) = self.burn.or_else(|| self.start_burn(rocket, planner, system)) else {
+            rocket.engine_on = false;
+            return message;
+        };
+        self.burn = Some((node, remaining));
+
+        let Some(stage) = rocket.propulsion.active() else {
+            return self.finish(rocket, planner, system, sun, target).or(message);
+        };
+        if remaining.length() < CUTOFF {
+            return self.finish(rocket, planner, system, sun, target).or(message);
+        }
+
+        // along what is left, throttled so the last step does not overshoot
+        let full = stage.thrust / rocket.propulsion.mass() * dt;
+        rocket.attitude = Attitude::Manual;
+        rocket.rotation = remaining.angle();
+        rocket.throttle = (remaining.length() / full).min(1.0);
+        rocket.engine_on = true;
+        message
+    }
+
+    // the engine gave `change` m/s over the last step
+    pub fn delivered(&mut self, change: Vec2) {
+        if let Some((_, remaining)) = &mut self.burn {
+            *remaining -= change;
+        }
+    }
+
+    // the next planned burn once its half burn time before the node has come, as the
+    // world velocity change the node asks for
+    fn start_burn(&self, rocket: &RocketInstance, planner: &Planner, system: &System) -> Option<(f64, Vec2)> {
+        let now = system.time;
+        if planner.until_burn(now, &rocket.propulsion)? > 0.0 {
+            return None;
+        }
+        let next = planner.next()?;
+        let primary = system.bodies[rocket.primary];
+        let at_node = coast(&rocket.body, &primary, next.time - now).unwrap_or(rocket.body);
+        let change = next.velocity_change(at_node.position - primary.position, at_node.velocity - primary.velocity);
+        Some((next.time, change))
+    }
+
+    fn finish(
+        &mut self,
+        rocket: &mut RocketInstance,
+        planner: &mut Planner,
+        system: &System,
+        sun: usize,
+        target: Option<usize>,
+    ) -> Option<String> {
+        let (node, remaining) = self.burn.take()?;
+        rocket.engine_on = false;
+        if planner.next().is_some_and(|next| next.time == node) {
+            planner.remove(0);
+        }
+
+        let captured = self.leg == Leg::Capture
+            && Some(rocket.primary) == target
+            && is_stable(
+                &system.elements(&rocket.body, rocket.primary),
+                &system.bodies[rocket.primary],
+                system.sphere_of_influence(rocket.primary, sun),
+            );
+        if captured {
+            self.leg = Leg::Arrived;
+        }
+        Some(if remaining.length() >= CUTOFF {
+            format!("autopilot: out of propellant with {:.1} m/s to go", remaining.length())
+        } else if captured {
+            "autopilot: captured, transfer complete".to_string()
+        } else {
+            "autopilot: burn complete".to_string()
+        })
+    }
+
+    // the next leg once the rocket gets there: the correction once it orbits the star,
+    // the trim once it reaches the target, then the capture or the aerocapture pass
+    fn plan_leg(
+        &mut self,
+        rocket: &RocketInstance,
+        planner: &mut Planner,
+        system: &System,
+        sun: usize,
+        target: Option<usize>,
+    ) -> Option<String> {
+        let (Some(arrival), Some(target)) = (self.arrival, target) else {
+            return None;
+        };
+        if self.burn.is_some() {
+            return None;
+        }
+        let (maneuver, name) = match self.leg {
+            Leg::Departure if rocket.primary == sun => {
+                self.leg = Leg::Cruise;
+                (correction(rocket, system, sun, target, arrival), "mid-course correction")
+            }
+            Leg::Cruise if rocket.primary == target => {
+                self.leg = Leg::Approach;
+                let periapsis = self.arrival_periapsis(rocket, system, target);
+                (trim(rocket, system, target, periapsis), "approach trim")
+            }
+            Leg::Approach if planner.next().is_none() => {
+                if self.aerocapture && self.arrival_periapsis(rocket, system, target).is_some() {
+                    self.leg = Leg::Aerocapture;
+                    let orbit = system.elements(&rocket.body, target);
+                    let altitude = orbit.periapsis - system.bodies[target].radius;
+                    return Some(format!("autopilot: aerocapture pass at {:.1} km", altitude / 1000.0));
+                }
+                self.leg = Leg::Capture;
+                (capture(rocket, system, target), "capture burn")
+            }
+            Leg::Aerocapture if !rocket.in_atmosphere(system) => {
+                let orbit = system.elements(&rocket.body, target);
+                if orbit.is_bound() {
+                    self.leg = Leg::Capture;
+                    (raise(rocket, system, target), "periapsis raise")
+                } else if orbit.time_to_periapsis(G * system.bodies[target].mass) < 0.0 {
+                    self.arrival = None;
+                    return Some("autopilot: aerocapture failed, skipped out of the air".to_string());
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        };
+        Some(match maneuver {
+            Some(maneuver) => {
+                planner.add(maneuver);
+                format!("autopilot: {name} of {:.1} m/s planned", maneuver.delta_v())
+            }
+            None => format!("autopilot: no {name} found, flying on"),
+        })
+    }
+
+    // distance from the target's center to aim the closest approach at: low in the air
+    // for an aerocapture, at the capture altitude otherwise. `None` for an aerocapture
+    // the target's air cannot give.
+    fn arrival_periapsis(&self, rocket: &RocketInstance, system: &System, target: usize) -> Option<f64> {
+        let planet = system.bodies[target];
+        if !self.aerocapture {
+            return Some(planet.radius + CAPTURE_ALTITUDE);
+        }
+        let orbit = system.elements(&rocket.body, target);
+        let arriving = approach(&orbit, G * planet.mass)?;
+        let airframe = rocket.airframe;
+        let ballistic = airframe.drag_coefficient * airframe.area / rocket.body.mass;
+        aerocapture_periapsis(&planet, arriving.excess_speed, ballistic, planet.radius + AEROCAPTURE_APOAPSIS)
+    }
+
+    // one line for the game screen
+    pub fn status(&self, planner: &Planner, propulsion: &Propulsion, now: f64) -> String {
+        if !self.engaged {
+            return "autopilot: off (click here to engage)".to_string();
+        }
+        let leg = match self.arrival {
+            Some(_) => format!(" ({})", self.leg.name()),
+            None => String::new(),
+        };
+        let doing = match (self.burn, planner.until_burn(now, propulsion)) {
+            (Some((_, remaining)), _) => format!("burning, {:.1} m/s to go", remaining.length()),
+            (None, Some(until)) => format!("next burn in {}", format_duration(until.max(0.0))),
+            (None, None) => "no burns planned".to_string(),
+        };
+        format!("autopilot{leg}: {doing} (click or steer to take over)")
+    }
+}
+
+// burn a day out that puts the rocket on a conic around the star that passes the target
+// at the capture altitude when the transfer arrives
+fn correction(rocket: &RocketInstance, system: &System, sun: usize, target: usize, arrival: f64) -> Option<Maneuver> {
+    let star = system.bodies[sun];
+    let planet = system.bodies[target];
+    let now = system.time;
+    let time_of_flight = arrival - now - CORRECTION_DELAY;
+    if time_of_flight <= 0.0 {
+        return None;
+    }
+    let craft = coast(&rocket.body, &star, CORRECTION_DELAY)?;
+    let meeting = coast(&planet, &star, arrival - now)?;
+    let mu = G * star.mass;
+    let from = craft.position - star.position;
+    let velocity = craft.velocity - star.velocity;
+    let to = meeting.position - star.position;
+
+    // aim at the planet to find how the rocket comes in, then beside it to swing by
+    let (_, arriving) = lambert(mu, from, to, time_of_flight, true)?;
+    let excess = arriving - (meeting.velocity - star.velocity);
+    let aim = to + impact_offset(G * planet.mass, planet.radius + CAPTURE_ALTITUDE, excess);
+    let (leaving, _) = lambert(mu, from, aim, time_of_flight, true)?;
+
+    let change = leaving - velocity;
+    let (prograde, radial) = Maneuver::frame(from, velocity);
+    Some(Maneuver {
+        time: now + CORRECTION_DELAY,
+        prograde: change.dot(prograde),
+        radial: change.dot(radial),
+    })
+}
+
+// across the radius shortly after arriving, to move the closest approach to `periapsis`
+fn trim(rocket: &RocketInstance, system: &System, target: usize, periapsis: Option<f64>) -> Option<Maneuver> {
+    let planet = system.bodies[target];
+    let at = coast(&rocket.body, &planet, TRIM_DELAY)?;
+    let (position, velocity) = (at.position - planet.position, at.velocity - planet.velocity);
+    let change = periapsis_trim(G * planet.mass, position, velocity, periapsis?)?;
+    let (prograde, radial) = Maneuver::frame(position, velocity);
+    Some(Maneuver {
+        time: system.time + TRIM_DELAY,
+        prograde: change.dot(prograde),
+        radial: change.dot(radial),
+    })
+}
+
+// retrograde at periapsis, down to a circular orbit there
+fn capture(rocket: &RocketInstance, system: &System, target: usize) -> Option<Maneuver> {
+    let planet = system.bodies[target];
+    let mu = G * planet.mass;
+    let arriving = approach(&system.elements(&rocket.body, target), mu)?;
+    if arriving.periapsis <= planet.radius {
+        return None;
+    }
+    Some(Maneuver {
+        time: system.time + arriving.time_to_periapsis,
+        prograde: capture_burn(mu, &arriving, arriving.periapsis),
+        radial: 0.0,
+    })
+}
+
+// prograde at the next apoapsis, lifting the periapsis out of the air after an aerocapture
+fn raise(rocket: &RocketInstance, system: &System, target: usize) -> Option<Maneuver> {
+    let planet = system.bodies[target];
+    let mu = G * planet.mass;
+    let orbit = system.elements(&rocket.body, target);
+    if !orbit.is_bound() {
+        return None;
+    }
+    let to_periapsis = orbit.time_to_periapsis(mu);
+    let to_apoapsis = if to_periapsis > orbit.period / 2.0 {
+        to_periapsis - orbit.period / 2.0
+    } else {
+        to_periapsis + orbit.period / 2.0
+    };
+    Some(Maneuver {
+        time: system.time + to_apoapsis,
+        prograde: periapsis_raise(mu, &orbit, planet.radius + CAPTURE_ALTITUDE),
+        radial: 0.0,
+    })
+}
@@ -0,0 +1,64 @@
+// the only place world meters turn into screen pixels. physics state never holds
+// screen coordinates; everything that draws asks the camera where a world point lands.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::physics::Vec2;
+use crate::{HEIGHT, SCALE, WIDTH};
+
+// zoom range: the whole inner solar system down to a few hundred meters per pixel
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 1.0e7;
+
+// zoom change per mouse wheel notch
+const ZOOM_STEP: f64 = 1.25;
+
+#[derive(Clone, Copy, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Camera {
+    pub center: Vec2, // world point drawn in the middle of the screen
+    pub zoom: f64,    // 1 shows the solar system at `SCALE`
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+
+    // screen pixels per world meter
+    pub fn scale(&self) -> f64 {
+        SCALE * self.zoom
+    }
+
+    pub fn to_screen(self, world: Vec2) -> (i32, i32) {
+        let offset = (world - self.center) * self.scale();
+        (
+            (offset.x + WIDTH as f64 / 2.0) as i32,
+            (offset.y + HEIGHT as f64 / 2.0) as i32,
+        )
+    }
+
+    // the world point drawn at a screen pixel
+    pub fn to_world(self, screen: (i32, i32)) -> Vec2 {
+        let offset = Vec2::new(
+            screen.0 as f64 - WIDTH as f64 / 2.0,
+            screen.1 as f64 - HEIGHT as f64 / 2.0,
+        );
+        self.center + offset / self.scale()
+    }
+
+    // world length as a screen length
+    pub fn to_pixels(self, meters: f64) -> f64 {
+        meters * self.scale()
+    }
+
+    // positive notches zoom in, negative zoom out
+    pub fn zoom_by(&mut self, notches: i32) {
+        self.zoom = (self.zoom * ZOOM_STEP.powi(notches)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    pub fn follow(&mut self, target: Vec2) {
+        self.center = target;
+    }
+}
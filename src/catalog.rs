@@ -0,0 +1,674 @@
+// rocket catalog (src/data/rockets.json): vehicles described stage by stage with their
+// engines, so the propulsion model can be fed real data.
+//
+// schema history:
+//   1 - one flat record per vehicle (total mass, total thrust, fuel capacity), no isp.
+//       files without `schema_version` are version 1 and are migrated on load.
+//   2 - per-stage dry/propellant mass, engines with sea-level and vacuum thrust and isp,
+//       strap-on boosters, fairing and payload capacity. optional drag coefficient and
+//       structural, heating, speed and altitude limits, defaulted when missing. the
+//       speed and altitude limits are version 1's `max_speed` and `max_altitude`.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::json;
+use crate::physics::{Propulsion, Stage, STANDARD_GRAVITY};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+// the catalog shipped with the game, compiled in since the wasm build has no file system
+const BUILTIN: &str = include_str!("data/rockets.json");
+
+// why a catalog could not be read
+#[derive(Debug)]
+pub enum CatalogError {
+    Json(serde_json::Error), // not json, or no `rockets` list
+    UnknownVersion(u64),
+    // rocket number `index` is malformed; `field` is the path inside it, e.g. `stages[1].engine.isp_vac_s`
+    Entry { index: usize, field: String, message: String },
+    Duplicate { index: usize, name: String },
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatalogError::Json(err) => write!(f, "rocket catalog is not valid json: {err}"),
+            CatalogError::UnknownVersion(version) => write!(
+                f,
+                "unknown catalog schema_version {version}, this build reads up to {SCHEMA_VERSION}"
+            ),
+            CatalogError::Entry { index, field, message } => {
+                write!(f, "rocket {index}, field `{field}`: {message}")
+            }
+            CatalogError::Duplicate { index, name } => {
+                write!(f, "rocket {index}: `{name}` is already in the catalog")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+impl From<serde_json::Error> for CatalogError {
+    fn from(err: serde_json::Error) -> Self {
+        CatalogError::Json(err)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RocketSpec {
+    pub name: String,
+    pub manufacturer: String,
+    pub height_m: f64,
+    pub diameter_m: f64,
+    pub stages: Vec<StageSpec>, // in firing order
+    #[serde(default)]
+    pub fairing: Option<FairingSpec>,
+    pub payload: PayloadSpec,
+    #[serde(default = "default_drag_coefficient")]
+    pub drag_coefficient: f64, // over the frontal area, `diameter_m` wide
+    #[serde(default)]
+    pub limits: LimitsSpec,
+    pub reliability: f64, // chance of a successful flight, 0..1
+    pub cost_usd: u64,
+    pub price_usd: u64,
+    pub cooldown_s: u32, // time between launches
+    pub construction_speed: u32,
+    pub image: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StageSpec {
+    pub name: String,
+    pub dry_mass_kg: f64,
+    pub propellant_mass_kg: f64,
+    pub burn_time_s: f64, // at full vacuum thrust, must agree with the propellant and engine
+    pub engine: EngineSpec,
+    #[serde(default)]
+    pub boosters: Option<BoosterSpec>, // strap-ons firing together with this stage
+}
+
+// identical strap-on boosters; masses are per booster
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoosterSpec {
+    pub count: u32,
+    pub dry_mass_kg: f64,
+    pub propellant_mass_kg: f64,
+    pub burn_time_s: f64,
+    pub engine: EngineSpec,
+}
+
+// `count` identical engines; thrust is per engine. vacuum-only engines have no
+// sea-level figures.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EngineSpec {
+    pub name: String,
+    pub count: u32,
+    pub thrust_sl_n: Option<f64>,
+    pub thrust_vac_n: f64,
+    pub isp_sl_s: Option<f64>,
+    pub isp_vac_s: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FairingSpec {
+    pub mass_kg: f64,
+    pub diameter_m: f64,
+    pub length_m: f64,
+}
+
+// what the vehicle can lift
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PayloadSpec {
+    pub leo_kg: f64,
+    #[serde(default)]
+    pub gto_kg: Option<f64>,
+}
+
+// what the airframe survives before it breaks up
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LimitsSpec {
+    pub max_q_pa: f64,   // dynamic pressure
+    pub max_load_g: f64,   // thrust and drag over weight at standard gravity
+    #[serde(default = "default_max_heat_flux")]
+    pub max_heat_flux_kw_m2: f64, // at the nose
+    #[serde(default = "default_max_heat_load")]
+    pub max_heat_load_mj_m2: f64, // heat flux summed over the flight
+    #[serde(default = "default_max_speed")]
+    pub max_speed_m_s: f64, // through the air
+    #[serde(default = "default_max_altitude")]
+    pub max_altitude_m: f64, // rated ceiling, shown as a hint and never enforced
+}
+
+// limits for entries without their own: about twice what a full-throttle ascent sees on
+// most vehicles, so only a bad trajectory (or a fall back through the air) breaks them
+const DEFAULT_MAX_Q: f64 = 80_000.0;
+const DEFAULT_MAX_LOAD: f64 = 20.0;
+
+// heating the payload's shield takes, about what a mars entry or aerocapture puts on one
+const DEFAULT_MAX_HEAT_FLUX: f64 = 1000.0;
+const DEFAULT_MAX_HEAT_LOAD: f64 = 150.0;
+
+fn default_max_heat_flux() -> f64 {
+    DEFAULT_MAX_HEAT_FLUX
+}
+
+fn default_max_heat_load() -> f64 {
+    DEFAULT_MAX_HEAT_LOAD
+}
+
+// an orbital launcher: faster than low orbit through the top of the air, and up to a
+// high parking orbit before it has to be going round
+const DEFAULT_MAX_SPEED: f64 = 8000.0;
+const DEFAULT_MAX_ALTITUDE: f64 = 300_000.0;
+
+fn default_max_speed() -> f64 {
+    DEFAULT_MAX_SPEED
+}
+
+fn default_max_altitude() -> f64 {
+    DEFAULT_MAX_ALTITUDE
+}
+
+// a slender rocket, averaged over the subsonic and supersonic part of the ascent
+const DEFAULT_DRAG_COEFFICIENT: f64 = 0.3;
+
+fn default_drag_coefficient() -> f64 {
+    DEFAULT_DRAG_COEFFICIENT
+}
+
+impl Default for LimitsSpec {
+    fn default() -> Self {
+        Self {
+            max_q_pa: DEFAULT_MAX_Q,
+            max_load_g: DEFAULT_MAX_LOAD,
+            max_heat_flux_kw_m2: DEFAULT_MAX_HEAT_FLUX,
+            max_heat_load_mj_m2: DEFAULT_MAX_HEAT_LOAD,
+            max_speed_m_s: DEFAULT_MAX_SPEED,
+            max_altitude_m: DEFAULT_MAX_ALTITUDE,
+        }
+    }
+}
+
+impl EngineSpec {
+    // total thrust and isp of all engines, at sea level or in vacuum
+    fn performance(&self, sea_level: bool) -> (f64, f64) {
+        let (thrust, isp) = match (sea_level, self.thrust_sl_n, self.isp_sl_s) {
+            (true, Some(thrust), Some(isp)) => (thrust, isp),
+            _ => (self.thrust_vac_n, self.isp_vac_s),
+        };
+        (thrust * self.count as f64, isp)
+    }
+
+    // s to burn `propellant` kg at full vacuum thrust
+    fn burn_time(&self, propellant: f64) -> f64 {
+        propellant * self.isp_vac_s * STANDARD_GRAVITY / (self.thrust_vac_n * self.count as f64)
+    }
+}
+
+// a bad value in an entry: the field's path and what is wrong with it
+type Invalid = (String, &'static str);
+
+fn check(ok: bool, field: impl Into<String>, message: &'static str) -> Result<(), Invalid> {
+    if ok {
+        Ok(())
+    } else {
+        Err((field.into(), message))
+    }
+}
+
+fn check_mass(value: f64, field: String) -> Result<(), Invalid> {
+    check(value.is_finite() && value >= 0.0, field, "must be a mass of zero or more kg")
+}
+
+fn check_positive(value: f64, field: String) -> Result<(), Invalid> {
+    check(value.is_finite() && value > 0.0, field, "must be positive")
+}
+
+// a listed burn time is redundant with the propellant, thrust and isp; it only has to
+// agree with them to this fraction
+const BURN_TIME_TOLERANCE: f64 = 0.01;
+
+fn check_burn_time(burn_time: f64, propellant: f64, engine: &EngineSpec, field: String) -> Result<(), Invalid> {
+    let expected = engine.burn_time(propellant);
+    check(
+        (burn_time - expected).abs() <= BURN_TIME_TOLERANCE * expected,
+        field,
+        "does not match the propellant burned at the engines' vacuum thrust and isp",
+    )
+}
+
+impl EngineSpec {
+    fn validate(&self, path: &str) -> Result<(), Invalid> {
+        check(self.count > 0, format!("{path}.count"), "needs at least one engine")?;
+        check_positive(self.thrust_vac_n, format!("{path}.thrust_vac_n"))?;
+        check_positive(self.isp_vac_s, format!("{path}.isp_vac_s"))?;
+        if let Some(thrust) = self.thrust_sl_n {
+            check_positive(thrust, format!("{path}.thrust_sl_n"))?;
+        }
+        if let Some(isp) = self.isp_sl_s {
+            check_positive(isp, format!("{path}.isp_sl_s"))?;
+        }
+        Ok(())
+    }
+}
+
+impl RocketSpec {
+    // values the physics cannot work with (no stages, no thrust, negative masses)
+    fn validate(&self) -> Result<(), Invalid> {
+        check(!self.name.trim().is_empty(), "name", "must not be empty")?;
+        check(!self.stages.is_empty(), "stages", "needs at least one stage")?;
+        for (i, stage) in self.stages.iter().enumerate() {
+            let path = format!("stages[{i}]");
+            check_mass(stage.dry_mass_kg, format!("{path}.dry_mass_kg"))?;
+            check_mass(stage.propellant_mass_kg, format!("{path}.propellant_mass_kg"))?;
+            stage.engine.validate(&format!("{path}.engine"))?;
+            check_burn_time(stage.burn_time_s, stage.propellant_mass_kg, &stage.engine, format!("{path}.burn_time_s"))?;
+            if let Some(boosters) = &stage.boosters {
+                check(boosters.count > 0, format!("{path}.boosters.count"), "needs at least one booster")?;
+                check_mass(boosters.dry_mass_kg, format!("{path}.boosters.dry_mass_kg"))?;
+                check_positive(boosters.propellant_mass_kg, format!("{path}.boosters.propellant_mass_kg"))?;
+                boosters.engine.validate(&format!("{path}.boosters.engine"))?;
+                check_burn_time(
+                    boosters.burn_time_s,
+                    boosters.propellant_mass_kg,
+                    &boosters.engine,
+                    format!("{path}.boosters.burn_time_s"),
+                )?;
+            }
+        }
+        if let Some(fairing) = &self.fairing {
+            check_mass(fairing.mass_kg, "fairing.mass_kg".to_string())?;
+        }
+        check_positive(self.diameter_m, "diameter_m".to_string())?;
+        check_positive(self.drag_coefficient, "drag_coefficient".to_string())?;
+        check_positive(self.limits.max_q_pa, "limits.max_q_pa".to_string())?;
+        check_positive(self.limits.max_load_g, "limits.max_load_g".to_string())?;
+        check_positive(self.limits.max_heat_flux_kw_m2, "limits.max_heat_flux_kw_m2".to_string())?;
+        check_positive(self.limits.max_heat_load_mj_m2, "limits.max_heat_load_mj_m2".to_string())?;
+        check_positive(self.limits.max_speed_m_s, "limits.max_speed_m_s".to_string())?;
+        check_positive(self.limits.max_altitude_m, "limits.max_altitude_m".to_string())?;
+        check((0.0..=1.0).contains(&self.reliability), "reliability", "must be between 0 and 1")
+    }
+
+    // m^2 the air pushes against, nose first
+    pub fn frontal_area(&self) -> f64 {
+        std::f64::consts::PI * self.diameter_m * self.diameter_m / 4.0
+    }
+
+    // m, the blunt nose the heating is worked out for
+    pub fn nose_radius(&self) -> f64 {
+        self.diameter_m / 2.0
+    }
+
+    pub fn fairing_mass(&self) -> f64 {
+        self.fairing.as_ref().map_or(0.0, |f| f.mass_kg)
+    }
+
+    // everything on the pad, with `payload` kg on top
+    pub fn liftoff_mass(&self, payload: f64) -> f64 {
+        let stages: f64 = self
+            .stages
+            .iter()
+            .map(|s| {
+                let boosters = s.boosters.as_ref().map_or(0.0, |b| {
+                    b.count as f64 * (b.dry_mass_kg + b.propellant_mass_kg)
+                });
+                s.dry_mass_kg + s.propellant_mass_kg + boosters
+            })
+            .sum();
+        stages + self.fairing_mass() + payload
+    }
+
+    // physics stages in firing order, carrying `payload` kg and the fairing. a stage
+    // with boosters becomes two: both burning together until the boosters run dry
+    // (dropping their dry mass), then the core alone with the propellant it has left.
+    // the first stage and its boosters use sea-level figures, the others vacuum.
+    pub fn propulsion(&self, payload: f64) -> Propulsion {
+        let mut stages = Vec::new();
+        for (i, spec) in self.stages.iter().enumerate() {
+            let sea_level = i == 0;
+            let (thrust, isp) = spec.engine.performance(sea_level);
+            let mut propellant = spec.propellant_mass_kg;
+
+            if let Some(boosters) = &spec.boosters {
+                let count = boosters.count as f64;
+                let (booster_thrust, booster_isp) = boosters.engine.performance(sea_level);
+                let booster_thrust = booster_thrust * count;
+                let booster_propellant = boosters.propellant_mass_kg * count;
+
+                let core_flow = thrust / (isp * STANDARD_GRAVITY);
+                let booster_flow = booster_thrust / (booster_isp * STANDARD_GRAVITY);
+                let together = booster_propellant / booster_flow;
+                let core_burned = (core_flow * together).min(propellant);
+                propellant -= core_burned;
+
+                let combined_flow = core_flow + booster_flow;
+                stages.push(Stage::new(
+                    boosters.dry_mass_kg * count,
+                    booster_propellant + core_burned,
+                    thrust + booster_thrust,
+                    (thrust + booster_thrust) / (combined_flow * STANDARD_GRAVITY),
+                ));
+            }
+            stages.push(Stage::new(spec.dry_mass_kg, propellant, thrust, isp));
+        }
+        Propulsion::new(stages, payload + self.fairing_mass())
+    }
+}
+
+// version 1 record, kept only to read old files
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct RocketV1 {
+    pub name: String,
+    pub manufacturer: String,
+    pub height: f64,
+    pub diameter: f64,
+    pub mass: f64,          // kg on the pad
+    pub thrust: f64,        // N at liftoff
+    pub fuel_capacity: f64, // kg of propellant, all stages
+    pub stages: u8,
+    pub payload_capacity: f64, // kg to low earth orbit
+    pub reliability: f64,
+    pub cost: u64,
+    pub cooldown_time: u32,
+    pub price: u64,
+    pub image: String,
+    pub construction_speed: u32,
+    pub max_speed: f64,    // m/s
+    pub max_altitude: f64, // m
+}
+
+// typical values standing in for the engine data version 1 never had
+const MIGRATED_ISP_SL: f64 = 280.0;
+const MIGRATED_ISP_VAC: f64 = 310.0;
+const MIGRATED_UPPER_ISP: f64 = 340.0;
+// each stage is this many times heavier than the one above it
+const MIGRATED_STAGE_RATIO: f64 = 4.0;
+
+// turn a flat version 1 record into stages. the totals are kept (liftoff mass, liftoff
+// thrust, propellant); how they split between stages and the isp are estimates.
+pub fn migrate_v1(old: &RocketV1) -> RocketSpec {
+    let count = old.stages.max(1) as i32;
+    let propellant = old.fuel_capacity.clamp(0.0, old.mass * 0.95);
+    let dry = old.mass - propellant;
+    // share of the vehicle in each stage, bottom first
+    let weights: Vec<f64> = (0..count).map(|i| MIGRATED_STAGE_RATIO.powi(count - 1 - i)).collect();
+    let total: f64 = weights.iter().sum();
+
+    let stages = weights
+        .iter()
+        .enumerate()
+        .map(|(i, weight)| {
+            let share = weight / total;
+            let first = i == 0;
+            // upper stages get thrust in proportion to their size
+            let thrust = old.thrust * weight / weights[0];
+            let engine = EngineSpec {
+                name: "unknown".to_string(),
+                count: 1,
+                thrust_sl_n: first.then_some(thrust),
+                thrust_vac_n: if first { thrust * MIGRATED_ISP_VAC / MIGRATED_ISP_SL } else { thrust },
+                isp_sl_s: first.then_some(MIGRATED_ISP_SL),
+                isp_vac_s: if first { MIGRATED_ISP_VAC } else { MIGRATED_UPPER_ISP },
+            };
+            let flow = engine.thrust_vac_n / (engine.isp_vac_s * STANDARD_GRAVITY);
+            StageSpec {
+                name: format!("stage {}", i + 1),
+                dry_mass_kg: dry * share,
+                propellant_mass_kg: propellant * share,
+                burn_time_s: propellant * share / flow,
+                engine,
+                boosters: None,
+            }
+        })
+        .collect();
+
+    RocketSpec {
+        name: old.name.clone(),
+        manufacturer: old.manufacturer.clone(),
+        height_m: old.height,
+        diameter_m: old.diameter,
+        stages,
+        fairing: None,
+        payload: PayloadSpec {
+            leo_kg: old.payload_capacity,
+            gto_kg: None,
+        },
+        drag_coefficient: DEFAULT_DRAG_COEFFICIENT,
+        limits: LimitsSpec {
+            max_speed_m_s: old.max_speed,
+            max_altitude_m: old.max_altitude,
+            ..LimitsSpec::default()
+        },
+        reliability: old.reliability,
+        cost_usd: old.cost,
+        price_usd: old.price,
+        cooldown_s: old.cooldown_time,
+        construction_speed: old.construction_speed,
+        image: old.image.clone(),
+    }
+}
+
+// rocket number `index`, naming the offending field when it does not fit `T`
+fn parse_entry<T: DeserializeOwned>(index: usize, value: serde_json::Value) -> Result<T, CatalogError> {
+    json::parse_entry(value).map_err(|(field, message)| CatalogError::Entry { index, field, message })
+}
+
+// read a catalog of any known version, migrating older ones to the current schema
+pub fn parse_catalog(contents: &str) -> Result<Vec<RocketSpec>, CatalogError> {
+    let mut value: serde_json::Value = serde_json::from_str(contents)?;
+    let version = value.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(1);
+    if version > SCHEMA_VERSION as u64 || version == 0 {
+        return Err(CatalogError::UnknownVersion(version));
+    }
+    let entries = match value.get_mut("rockets").map(serde_json::Value::take) {
+        Some(serde_json::Value::Array(entries)) => entries,
+        _ => return Err(CatalogError::Json(serde::de::Error::custom("expected a `rockets` list"))),
+    };
+
+    let mut rockets = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.into_iter().enumerate() {
+        let spec = match version {
+            1 => migrate_v1(&parse_entry::<RocketV1>(index, entry)?),
+            _ => parse_entry::<RocketSpec>(index, entry)?,
+        };
+        spec.validate().map_err(|(field, message)| CatalogError::Entry {
+            index,
+            field,
+            message: message.to_string(),
+        })?;
+        rockets.push(spec);
+    }
+    Ok(rockets)
+}
+
+// a parsed catalog indexed for lookups, in file order
+#[derive(Clone, Debug, Default)]
+pub struct RocketRegistry {
+    rockets: Vec<RocketSpec>,
+    by_name: HashMap<String, usize>, // lowercase name to index
+}
+
+impl RocketRegistry {
+    pub fn from_json(contents: &str) -> Result<Self, CatalogError> {
+        let rockets = parse_catalog(contents)?;
+        let mut by_name = HashMap::new();
+        for (index, spec) in rockets.iter().enumerate() {
+            if by_name.insert(spec.name.to_lowercase(), index).is_some() {
+                return Err(CatalogError::Duplicate {
+                    index,
+                    name: spec.name.clone(),
+                });
+            }
+        }
+        Ok(Self { rockets, by_name })
+    }
+
+    pub fn all(&self) -> &[RocketSpec] {
+        &self.rockets
+    }
+
+    pub fn len(&self) -> usize {
+        self.rockets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rockets.is_empty()
+    }
+
+    // by name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&RocketSpec> {
+        self.by_name.get(&name.to_lowercase()).map(|&i| &self.rockets[i])
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.by_name.get(&name.to_lowercase()).copied()
+    }
+
+    // everything one manufacturer builds, ignoring case
+    pub fn by_manufacturer<'a>(&'a self, manufacturer: &'a str) -> impl Iterator<Item = &'a RocketSpec> + 'a {
+        self.rockets
+            .iter()
+            .filter(move |spec| spec.manufacturer.eq_ignore_ascii_case(manufacturer))
+    }
+}
+
+// the built-in catalog, parsed on first use
+pub fn builtin() -> Result<&'static RocketRegistry, &'static CatalogError> {
+    static REGISTRY: OnceLock<Result<RocketRegistry, CatalogError>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RocketRegistry::from_json(BUILTIN)).as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the built-in catalog with `change` applied to it
+    fn catalog_with(change: impl FnOnce(&mut serde_json::Value)) -> String {
+        let mut catalog: serde_json::Value = serde_json::from_str(BUILTIN).unwrap();
+        change(&mut catalog);
+        catalog.to_string()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1.0e-9 * b.abs()
+    }
+
+    fn entry_error(contents: &str) -> (usize, String) {
+        match parse_catalog(contents) {
+            Err(CatalogError::Entry { index, field, .. }) => (index, field),
+            Err(err) => panic!("expected a malformed entry, got {err}"),
+            Ok(_) => panic!("expected a malformed entry, got a catalog"),
+        }
+    }
+
+    #[test]
+    fn builtin_catalog_loads() {
+        let registry = builtin().unwrap();
+        assert!(!registry.is_empty());
+        assert_eq!(registry.len(), registry.all().len());
+    }
+
+    #[test]
+    fn wrong_type_names_the_field() {
+        let contents = catalog_with(|c| c["rockets"][1]["stages"][0]["engine"]["isp_vac_s"] = "high".into());
+        assert_eq!(entry_error(&contents), (1, "stages[0].engine.isp_vac_s".to_string()));
+    }
+
+    #[test]
+    fn missing_field_names_the_field() {
+        let contents = catalog_with(|c| {
+            c["rockets"][2]["payload"].as_object_mut().unwrap().remove("leo_kg");
+        });
+        assert_eq!(entry_error(&contents), (2, "payload.leo_kg".to_string()));
+    }
+
+    #[test]
+    fn burn_time_must_match_the_engine() {
+        let contents = catalog_with(|c| c["rockets"][0]["stages"][1]["burn_time_s"] = 400.0.into());
+        assert_eq!(entry_error(&contents), (0, "stages[1].burn_time_s".to_string()));
+    }
+
+    // two records of the baseline version 1 catalog
+    const V1_CATALOG: &str = r#"{
+        "rockets": [
+            {
+                "name": "Falcon 9", "manufacturer": "SpaceX", "height": 70.0, "diameter": 3.7,
+                "mass": 549054.0, "thrust": 7607000.0, "fuel_capacity": 287400.0,
+                "max_speed": 7500.0, "max_altitude": 250000.0, "stages": 2,
+                "payload_capacity": 22800.0, "reliability": 0.98, "cost": 62000000,
+                "cooldown_time": 1209600, "price": 67000000, "image": "images/falcon9.jpg",
+                "construction_speed": 180
+            },
+            {
+                "name": "Neutron", "manufacturer": "Rocket Lab", "height": 40.0, "diameter": 5.0,
+                "mass": 250000.0, "thrust": 3400000.0, "fuel_capacity": 200000.0,
+                "max_speed": 9000.0, "max_altitude": 400000.0, "stages": 2,
+                "payload_capacity": 8000.0, "reliability": 0.9, "cost": 50000000,
+                "cooldown_time": 1209600, "price": 55000000, "image": "images/neutron.jpg",
+                "construction_speed": 180
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn version_1_migrates_and_round_trips() {
+        let old: serde_json::Value = serde_json::from_str(V1_CATALOG).unwrap();
+        let migrated = parse_catalog(V1_CATALOG).unwrap();
+        for (old, spec) in old["rockets"].as_array().unwrap().iter().zip(&migrated) {
+            let old: RocketV1 = serde_json::from_value(old.clone()).unwrap();
+            assert!(close(spec.liftoff_mass(0.0), old.mass));
+            assert!(close(spec.stages[0].engine.performance(true).0, old.thrust));
+            let propellant: f64 = spec.stages.iter().map(|s| s.propellant_mass_kg).sum();
+            assert!(close(propellant, old.fuel_capacity));
+            assert_eq!(spec.stages.len(), old.stages as usize);
+            assert_eq!(spec.payload.leo_kg, old.payload_capacity);
+            assert_eq!(spec.limits.max_speed_m_s, old.max_speed);
+            assert_eq!(spec.limits.max_altitude_m, old.max_altitude);
+        }
+
+        // written back out as version 2, the migrated catalog reads the same (up to the
+        // last bit serde_json may lose printing a float)
+        let rewritten = serde_json::json!({ "schema_version": SCHEMA_VERSION, "rockets": migrated });
+        let reread = parse_catalog(&rewritten.to_string()).unwrap();
+        assert_eq!(reread.len(), migrated.len());
+        for (reread, spec) in reread.iter().zip(&migrated) {
+            assert_eq!(reread.name, spec.name);
+            assert_eq!(reread.limits, spec.limits);
+            for (a, b) in reread.stages.iter().zip(&spec.stages) {
+                assert!(close(a.dry_mass_kg, b.dry_mass_kg));
+                assert!(close(a.propellant_mass_kg, b.propellant_mass_kg));
+                assert!(close(a.burn_time_s, b.burn_time_s));
+                assert_eq!(a.engine, b.engine);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let contents = catalog_with(|c| c["rockets"][3]["name"] = "FALCON 9".into());
+        match RocketRegistry::from_json(&contents) {
+            Err(CatalogError::Duplicate { index, name }) => {
+                assert_eq!(index, 3);
+                assert_eq!(name, "FALCON 9");
+            }
+            other => panic!("expected a duplicate, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn lookup_by_name_and_index() {
+        let registry = RocketRegistry::from_json(BUILTIN).unwrap();
+        let index = registry.index_of("electron").unwrap();
+        assert_eq!(registry.all()[index].name, "Electron");
+        assert_eq!(registry.get("ELECTRON"), Some(&registry.all()[index]));
+        assert_eq!(registry.get("Saturn V"), None);
+        assert_eq!(registry.index_of("Saturn V"), None);
+        assert!(registry.by_manufacturer("spacex").all(|r| r.manufacturer == "SpaceX"));
+    }
+}
@@ -0,0 +1,165 @@
+// text panels drawn on top of the game screen
+use turbo::prelude::*;
+
+use crate::physics::{Approach, Body, LaunchWindow, OrbitalElements, Propulsion, AU};
+use crate::rockets::{Airframe, Ascent};
+
+// right-hand column the panels are drawn in
+const PANEL_X: i32 = 1920 - 260;
+const LINE_HEIGHT: i32 = 10;
+
+// meters as km below a hundredth of an au, as au above
+pub fn format_distance(meters: f64) -> String {
+    if !meters.is_finite() {
+        "-".to_string()
+    } else if meters.abs() < 0.01 * AU {
+        format!("{:.1} km", meters / 1000.0)
+    } else {
+        format!("{:.4} AU", meters / AU)
+    }
+}
+
+// seconds as the largest sensible unit
+pub fn format_duration(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        "-".to_string()
+    } else if seconds.abs() < 120.0 {
+        format!("{seconds:.1} s")
+    } else if seconds.abs() < 7200.0 {
+        format!("{:.1} min", seconds / 60.0)
+    } else if seconds.abs() < 172_800.0 {
+        format!("{:.1} h", seconds / 3600.0)
+    } else {
+        format!("{:.1} days", seconds / 86400.0)
+    }
+}
+
+// draw `lines` as a column starting at `top` in the right-hand panel, returns the next free row
+pub fn draw_panel(lines: &[String], top: i32) -> i32 {
+    for (i, line) in lines.iter().enumerate() {
+        text!(line, x = PANEL_X, y = top + LINE_HEIGHT * i as i32, font = Font::S);
+    }
+    top + LINE_HEIGHT * (lines.len() as i32 + 1)
+}
+
+// orbit of the selected body around its primary
+pub fn draw_orbit_panel(name: &str, primary: Option<&str>, elements: Option<&OrbitalElements>, top: i32) -> i32 {
+    let mut lines = vec![format!("selected: {name}  (SELECT to cycle)")];
+    match (primary, elements) {
+        (Some(primary), Some(orbit)) => {
+            lines.push(format!("primary: {primary}"));
+            lines.push(format!("semi-major axis: {}", format_distance(orbit.semi_major_axis)));
+            lines.push(format!("eccentricity: {:.5}", orbit.eccentricity));
+            lines.push(format!("inclination: {:.1} deg", orbit.inclination.to_degrees()));
+            lines.push(format!(
+                "arg. of periapsis: {:.2} deg",
+                orbit.argument_of_periapsis.to_degrees()
+            ));
+            lines.push(format!("true anomaly: {:.2} deg", orbit.true_anomaly.to_degrees()));
+            lines.push(format!("period: {}", format_duration(orbit.period)));
+            lines.push(format!("periapsis: {}", format_distance(orbit.periapsis)));
+            lines.push(format!("apoapsis: {}", format_distance(orbit.apoapsis)));
+        }
+        _ => lines.push("primary: none".to_string()),
+    }
+    draw_panel(&lines, top)
+}
+
+// stages, propellant and the delta-v the rocket has left
+pub fn draw_propulsion_panel(propulsion: &Propulsion, top: i32) -> i32 {
+    let mut lines = vec![
+        format!("delta-v left: {:.0} m/s", propulsion.delta_v()),
+        format!("mass: {:.1} t", propulsion.mass() / 1000.0),
+    ];
+    match propulsion.active() {
+        Some(stage) => {
+            lines.push(format!("stages left: {}", propulsion.stages.len()));
+            lines.push(format!("propellant: {:.1} t", stage.propellant / 1000.0));
+            lines.push(format!("burn time left: {}", format_duration(stage.burn_time())));
+            lines.push(format!("thrust/weight: {:.2}", propulsion.thrust_to_weight()));
+        }
+        None => lines.push("no stages left".to_string()),
+    }
+    draw_panel(&lines, top)
+}
+
+// altitude, dynamic pressure, load, speed and heating against what the airframe takes
+pub fn draw_ascent_panel(ascent: &Ascent, airframe: &Airframe, top: i32) -> i32 {
+    let lines = vec![
+        format!(
+            "altitude: {} ({} rated ceiling {})",
+            format_distance(ascent.altitude),
+            if ascent.altitude > airframe.max_altitude { "above its" } else { "below its" },
+            format_distance(airframe.max_altitude)
+        ),
+        format!(
+            "dynamic pressure: {:.1} / {:.0} kPa",
+            ascent.dynamic_pressure / 1000.0,
+            airframe.max_q / 1000.0
+        ),
+        format!(
+            "max-Q: {:.1} kPa at {}",
+            ascent.max_q / 1000.0,
+            format_distance(ascent.max_q_altitude)
+        ),
+        format!("load: {:.1} / {:.0} g", ascent.load, airframe.max_load),
+        format!("airspeed: {:.0} / {:.0} m/s", ascent.airspeed, airframe.max_speed),
+        format!(
+            "heating: {:.0} / {:.0} kW/m2, peak {:.0}",
+            ascent.heat_flux / 1000.0,
+            airframe.max_heat_flux / 1000.0,
+            ascent.peak_heat_flux / 1000.0
+        ),
+        format!(
+            "heat load: {:.1} / {:.0} MJ/m2",
+            ascent.heat_load / 1.0e6,
+            airframe.max_heat_load / 1.0e6
+        ),
+    ];
+    draw_panel(&lines, top)
+}
+
+// a hyperbolic approach to `target`: where it passes, and what staying there costs
+pub fn draw_approach_panel(target: &str, planet: &Body, approach: &Approach, capture_burn: f64, top: i32) -> i32 {
+    let altitude = approach.periapsis - planet.radius;
+    let air = planet.atmosphere.map_or(0.0, |air| air.height);
+    let pass = if altitude <= 0.0 {
+        "impact"
+    } else if altitude < air {
+        "through the air, aerocapture"
+    } else {
+        "above the air"
+    };
+    let lines = vec![
+        format!("approaching {target}: periapsis {} ({pass})", format_distance(altitude)),
+        format!("v infinity: {:.0} m/s", approach.excess_speed),
+        format!("periapsis in: {}", format_duration(approach.time_to_periapsis)),
+        format!("capture burn to a circular orbit: {:.0} m/s", capture_burn.abs()),
+    ];
+    draw_panel(&lines, top)
+}
+
+// hohmann transfer between two planets and the countdown to its next window
+pub fn draw_transfer_panel(
+    origin: &str,
+    target: &str,
+    window: &LaunchWindow,
+    parking_burn: f64,
+    date: &str,
+    top: i32,
+) -> i32 {
+    let transfer = &window.transfer;
+    let lines = vec![
+        format!("transfer {origin} -> {target}"),
+        format!("departure: {:.0} m/s ({:.0} m/s from parking orbit)", transfer.departure_delta_v, parking_burn),
+        format!("arrival: {:.0} m/s", transfer.arrival_delta_v),
+        format!("time of flight: {}", format_duration(transfer.time_of_flight)),
+        format!(
+            "phase angle: {:.1} deg, needs {:.1} deg",
+            window.phase_angle.to_degrees(),
+            transfer.phase_angle.to_degrees()
+        ),
+        format!("next window: {date}, in {}", format_duration(window.wait)),
+    ];
+    draw_panel(&lines, top)
+}
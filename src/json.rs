@@ -0,0 +1,19 @@
+// helpers shared by the json loaders (the rocket catalog and planets.json)
+use serde::de::DeserializeOwned;
+
+// one entry of a list, or the path of the offending field inside it (e.g.
+// `stages[1].engine.isp_vac_s`) and what is wrong with it
+pub fn parse_entry<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, (String, String)> {
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let mut field = err.path().to_string();
+        let message = err.into_inner().to_string();
+        // a missing field is reported at its parent; name the field itself
+        if let Some(missing) = message.strip_prefix("missing field `").and_then(|m| m.strip_suffix('`')) {
+            field = match field.as_str() {
+                "." => missing.to_string(),
+                parent => format!("{parent}.{missing}"),
+            };
+        }
+        (field, message)
+    })
+}
@@ -1,16 +1,39 @@
-use log::{debug, info, Level};
+use log::{debug, error, info, Level};
+mod autopilot;
+mod camera;
+mod catalog;
+mod hud;
+mod json;
+mod milestones;
+mod physics;
 mod planet;
+mod planner;
+mod porkchop;
 mod rockets;
 mod scenemanager;
+mod solarsystem;
+mod timewarp;
+mod trail;
+mod trajectory;
 
-//use rockets::list_rockets;
-use std::thread;
+//use std::thread;
 use std::time::Duration;
 
+use autopilot::Autopilot;
+use camera::Camera;
+use physics::ephemeris::{calendar_date, format_date, julian_date};
+use physics::kepler::normalize_angle;
+use physics::{
+    approach, capture_burn, coast, craft_contact, departure_burn, escape_periapsis_angle, is_stable, launch_window,
+    merge_overlapping, CollisionEvent, Integrator, Maneuver, System, Vec2, AU, G,
+};
+use milestones::{Milestone, Milestones};
 use planet::Planet;
-use rockets::list_rockets;
-use rockets::Rocket;
+use planner::Planner;
+use rockets::{Failure, FlightStatus, Propagation, RocketInstance};
 use scenemanager::Screen;
+use solarsystem::load_solar_system_at;
+use timewarp::TimeWarp;
 use std::io::BufReader;
 
 // width and height, duh
@@ -18,21 +41,66 @@ use std::io::BufReader;
 const WIDTH: usize = 1920;
 const HEIGHT: usize = 1080;
 
-// astronomical unit in meters (average distance from Earth to the Sun)
-const AU: f64 = 149.6e6 * 1000.0;
+// scaling factor to convert astronomical units to screen coordinates
+const SCALE: f64 = 250.0 / AU;
 
-// gravitational constant in the SI unit of m^3 kg^-1 s^-2
-const G: f64 = 6.67428e-11;
+// the planet the game is about getting to
+const TARGET_PLANET: &str = "Mars";
 
-// used to avoid numerical instability in gravitational calculations at close distances
-const SOFTENING_FACTOR: f64 = 1.0e9;
+// the planet the rocket stands on when a run starts
+const LAUNCH_PLANET: &str = "Earth";
 
-// scaling factor to convert astronomical units to screen coordinates
-const SCALE: f64 = 250.0 / AU;
+// where on the launch planet the rocket stands, by local solar time, as the angle from
+// the sun-to-planet direction. the ground turns from midnight through dawn to noon, so
+// the dawn side faces along the orbit and a launch there gains the most speed
+const LAUNCH_SITES: [(&str, f64); 4] = [
+    ("dawn", std::f64::consts::FRAC_PI_2),
+    ("noon", std::f64::consts::PI),
+    ("dusk", -std::f64::consts::FRAC_PI_2),
+    ("midnight", 0.0),
+];
+
+// height of the circular orbit the transfer burn is planned from, in meters
+const PARKING_ALTITUDE: f64 = 200_000.0;
+
+// catalog rocket flown unless the player picks another one
+const DEFAULT_ROCKET: &str = "Falcon 9";
+
+// kg carried on top of the last stage, a small mars lander
+const MISSION_PAYLOAD: f64 = 1000.0;
+
+// camera zoom at the start of a run, close enough to see the launch planet's surface
+const LAUNCH_ZOOM: f64 = 2.0e4;
+
+// calendar date the simulation starts at unless the player picks another one
+const DEFAULT_START_DATE: (i32, u32, u32) = (2026, 1, 1);
 
-//pub const TIMESTEP: f64 = 1.0; // Decreased from 60.0 to make movement more gradual
-pub const TIMESTEP: f64 = 3600.0 * 24.0;
-//pub const TIMESTEP: f64 = 1.0; // Adjust this value to control simulation speed
+// throttle change per frame while UP or DOWN is held (full range in a second)
+const THROTTLE_RATE: f64 = 1.0 / 60.0;
+
+// radians the rocket turns per frame while LEFT or RIGHT is held (90 degrees a second)
+const TURN_RATE: f64 = std::f64::consts::FRAC_PI_2 / 60.0;
+
+// longest substep while the rocket is in an atmosphere, in seconds
+const ATMOSPHERE_STEP_SECONDS: f64 = 1.0;
+
+// frames a landing, crash or merge message stays on screen
+const MESSAGE_FRAMES: u32 = 300;
+
+// right edge of the clickable integrator label on the game screen, in pixels
+const INTEGRATOR_LABEL_WIDTH: i32 = 360;
+
+// right edge of the clickable porkchop plot label on the game screen, in pixels
+const PORKCHOP_LABEL_WIDTH: i32 = 210;
+
+// right edge of the clickable autopilot label on the game screen, in pixels
+const AUTOPILOT_LABEL_WIDTH: i32 = 300;
+
+// right edge of the clickable arrival label on the game screen, in pixels
+const ARRIVAL_LABEL_WIDTH: i32 = 330;
+
+// show energy and momentum drift of the simulation in the corner of the game screen
+const SHOW_DEBUG_OVERLAY: bool = true;
 
 turbo::cfg! {r#"
     name = "A day in Mars"
@@ -47,7 +115,10 @@ fn main() {
     env_logger::init();
 
     info!("starting up");
-    list_rockets();
+    match catalog::builtin() {
+        Ok(rockets) => info!("{} rockets in the catalog", rockets.len()),
+        Err(err) => error!("{}", err),
+    }
     // ...
 }
 
@@ -68,9 +139,23 @@ sink.sleep_until_end();
     struct GameState {
         is_ready: bool,
         screen: Screen,
-        rocket: Rocket,
+        rocket: RocketInstance,
+        rocket_name: String, // catalog entry the next flight is built from
         tick: u32,
         is_launching: bool,
+        start_jd: f64, // julian date at which system.time is zero
+        selected: usize, // body shown in the info panel and followed by the camera, planets.len() is the rocket
+        launch_site: usize, // index into LAUNCH_SITES
+        camera: Camera,
+        prediction_horizon: f64, // seconds the rocket's predicted path looks ahead
+        system: System,
+        warp: TimeWarp,
+        propagation: Propagation, // how the rocket coasts, picked on the title screen
+        message: String, // last landing, crash or merge
+        message_tick: u32,
+        planner: Planner, // maneuvers planned for the current rocket
+        autopilot: Autopilot, // flies the planned burns when engaged
+        milestones: Milestones, // reached this run
         planets: Vec<Planet>
 
     } = {
@@ -85,60 +170,280 @@ impl GameState {
 
         //    pub  fn new(x: f64, y: f64, radius: f64, color: u32, mass: f64) -> Self {
 
-        let mut sun = Planet::new(
-            0.0,
-            0.0,
-            100.0,
-            0x00FF_FF00,
-            1.98892_f64 * 10.0_f64.powi(30),
-        );
-        sun.sun = true;
-        let mut earth = Planet::new(
-            -1.0 * AU,
-            0.0,
-            160.0,
-            0x0064_95ED,
-            //            5.9742_f64 * 10.0_f64.powi(24),
-            5.9742_f64 * 10.0_f64.powi(24),
-        );
-        earth.y_vel = 29.783 * 1000.0;
-        let mut mars = Planet::new(
-            -1.524 * AU,
-            0.0,
-            120.0,
-            0x00BC_2732,
-            6.39_f64 * 10.0_f64.powi(23),
-        );
-        mars.y_vel = 24.077 * 1000.0;
-        let mut mercury = Planet::new(
-            0.387 * AU,
-            0.0,
-            80.0,
-            0x0050_4E51,
-            3.30_f64 * 10.0_f64.powi(23),
-        );
-        mercury.y_vel = -47.4 * 1000.0;
-        let mut venus = Planet::new(
-            0.723 * AU,
-            0.0,
-            140.0,
-            0x00FF_FFFF,
-            4.8685_f64 * 10.0_f64.powi(24),
-        );
-        venus.y_vel = -35.02 * 1000.0;
+        // bodies and masses come from src/data/planets.json, orbits from the ephemeris
+        let (year, month, day) = DEFAULT_START_DATE;
+        let start_jd = julian_date(year, month, day);
+        let (mut system, planets) = load_solar_system_at(start_jd);
+        // symplectic 4th order keeps earth and mars on closed orbits for decades
+        system.integrator = Integrator::Yoshida4;
 
-        let mut planets = vec![sun, earth, mars, mercury, venus];
-        //        let mut planets = vec![sun, earth];
-        let mut rocket = Rocket::new();
+        let rocket = spawn_rocket(DEFAULT_ROCKET).unwrap_or_default();
         Self {
             // Initialize all fields with default values
             tick: 0,
             is_ready: false,
             is_launching: false,
+            start_jd: start_jd,
+            selected: 1,
+            launch_site: 0,
+            camera: Camera::new(),
+            prediction_horizon: trajectory::PREDICTION_HORIZON,
+            system: system,
+            warp: TimeWarp::new(),
+            propagation: Propagation::default(),
+            message: String::new(),
+            message_tick: 0,
+            planner: Planner::new(),
+            autopilot: Autopilot::new(),
+            milestones: Milestones::new(),
             planets: planets,
             screen: Screen::Title,
             rocket: rocket,
+            rocket_name: DEFAULT_ROCKET.to_string(),
+        }
+    }
+}
+
+impl GameState {
+    // put the planets where they are on the chosen start date and restart the clock
+    fn start_run(&mut self) {
+        let (integrator, adaptive) = (self.system.integrator, self.system.adaptive);
+        let (system, planets) = load_solar_system_at(self.start_jd);
+        self.system = system;
+        self.system.integrator = integrator;
+        self.system.adaptive = adaptive;
+        self.planets = planets;
+        self.reset_rocket();
+        self.milestones = Milestones::new();
+        self.selected = self.planets.len();
+        self.camera.zoom = LAUNCH_ZOOM;
+        trail::clear_all();
+        trajectory::clear();
+    }
+
+    // a fresh rocket standing on the launch planet at the chosen launch site
+    fn reset_rocket(&mut self) {
+        let sun = sun_index(self);
+        let planet = self
+            .planets
+            .iter()
+            .position(|p| p.name == LAUNCH_PLANET)
+            .unwrap_or(if sun == 0 { 1 } else { 0 });
+        let away_from_sun = (self.system.bodies[planet].position - self.system.bodies[sun].position).angle();
+
+        self.message.clear();
+        self.planner.clear();
+        self.autopilot = Autopilot::new();
+        self.rocket = match spawn_rocket(&self.rocket_name) {
+            Ok(rocket) => rocket,
+            Err(err) => {
+                // an engineless stand-in, so the run still starts and shows why
+                self.notify(err);
+                RocketInstance::default()
+            }
+        };
+        self.rocket.propagation = self.propagation;
+        self.rocket.place_on_surface(&self.system, planet, away_from_sun + LAUNCH_SITES[self.launch_site].1);
+    }
+
+    // show `message` on the game screen for a while
+    fn notify(&mut self, message: String) {
+        log!("{}", message);
+        self.message = message;
+        self.message_tick = self.tick;
+    }
+
+    // the rocket touched a body: stick to it or wreck it
+    fn on_contact(&mut self, event: CollisionEvent) {
+        match event {
+            CollisionEvent::Landing { body, speed, offset } => {
+                self.rocket.place_on_surface(&self.system, body, offset.angle());
+                self.rocket.engine_on = false;
+                self.notify(format!("landed on {} at {:.1} m/s", self.planets[body].name, speed));
+            }
+            CollisionEvent::Crash { body, speed } => {
+                self.rocket.status = FlightStatus::Crashed { body };
+                self.rocket.engine_on = false;
+                self.notify(format!(
+                    "crashed into {} at {:.2} km/s - press START for a new rocket",
+                    self.planets[body].name,
+                    speed / 1000.0
+                ));
+            }
+            CollisionEvent::Merge { .. } => {}
+        }
+    }
+
+    // `absorbed` is gone from the system: drop its planet and move every index past it
+    fn on_merge(&mut self, survivor: usize, absorbed: usize) {
+        let survivor_after = if survivor > absorbed { survivor - 1 } else { survivor };
+        let shift = |i: usize| match i {
+            i if i == absorbed => survivor_after,
+            i if i > absorbed => i - 1,
+            i => i,
+        };
+
+        let gone = self.planets.remove(absorbed);
+        self.selected = shift(self.selected);
+        self.rocket.status = match self.rocket.status {
+            FlightStatus::Landed { body, longitude } => FlightStatus::Landed { body: shift(body), longitude },
+            FlightStatus::Crashed { body } => FlightStatus::Crashed { body: shift(body) },
+            status => status,
+        };
+        self.rocket.primary = shift(self.rocket.primary);
+        self.notify(format!("{} merged into {}", gone.name, self.planets[survivor_after].name));
+    }
+
+    // advance everything by `dt` seconds: engine, air, gravity, contacts and merges
+    fn step(&mut self, dt: f64) {
+        let sun = sun_index(self);
+        let bodies_before = self.system.bodies.clone();
+        let rocket_before = self.rocket.body;
+
+        let target = self.planets.iter().position(|p| p.name == TARGET_PLANET);
+        let flown = self
+            .autopilot
+            .fly(&mut self.rocket, &mut self.planner, &self.system, sun, target, dt);
+        if let Some(message) = flown {
+            self.notify(message);
+        }
+
+        // all planets are advanced together, substepping wherever bodies get close. a
+        // flying rocket goes along as a test particle unless it coasts on a conic
+        self.rocket.steer(&self.system);
+        let (thrust, burn) = self.rocket.ignite(dt);
+        self.autopilot.delivered(thrust * dt);
+        let drag = match self.rocket.aerodynamics(&self.system, thrust, dt) {
+            Ok(drag) => drag,
+            Err(failure) => {
+                self.notify(match failure {
+                    Failure::DynamicPressure(q) => format!("broke up at {:.1} kPa dynamic pressure", q / 1000.0),
+                    Failure::Overload(g) => format!("broke up under {g:.1} g"),
+                    Failure::Overheat(flux) => format!("burned up at {:.0} kW/m2", flux / 1000.0),
+                    Failure::HeatLoad(load) => format!("heat shield burned through after {:.0} MJ/m2", load / 1.0e6),
+                    Failure::Overspeed(speed) => format!("broke up at {speed:.0} m/s through the air"),
+                } + " - press START for a new rocket");
+                Vec2::ZERO
+            }
+        };
+        if burn.separated > 0 {
+            self.notify(match self.rocket.propulsion.stages.len() {
+                0 => "last stage spent, coasting".to_string(),
+                left => format!("stage separation, {left} left"),
+            });
+        }
+        let coasted = self.rocket.coast(&self.system, dt);
+        if self.rocket.status == FlightStatus::Flying && coasted.is_none() {
+            let rocket = std::slice::from_mut(&mut self.rocket.body);
+            self.system.advance_with_craft(dt, rocket, &[thrust + drag]);
+        } else {
+            self.system.advance(dt);
+        }
+
+        for (planet, body) in self.planets.iter_mut().zip(&self.system.bodies) {
+            planet.track(body, &self.system.bodies[sun]);
+        }
+
+        //state.rocket.rotation = state.rocket.velocity_y.atan2(state.rocket.velocity_x);
+        if let Some(primary) = self.rocket.update(&self.system, coasted, sun) {
+            let name = &self.planets[primary].name;
+            self.notify(if primary == sun {
+                format!("left the sphere of influence, now orbiting {name}")
+            } else {
+                format!("entered the sphere of influence of {name}")
+            });
+        }
+
+        if self.rocket.status == FlightStatus::Flying {
+            let contact = craft_contact(
+                &bodies_before,
+                &self.system.bodies,
+                &rocket_before,
+                &self.rocket.body,
+            );
+            if let Some(event) = contact {
+                self.on_contact(event);
+            }
+        }
+        for event in merge_overlapping(&mut self.system) {
+            if let CollisionEvent::Merge { survivor, absorbed } = event {
+                self.on_merge(survivor, absorbed);
+            }
+        }
+        self.check_milestones();
+    }
+
+    // a stable orbit around the target, however the rocket got there
+    fn check_milestones(&mut self) {
+        let sun = sun_index(self);
+        let Some(target) = self.planets.iter().position(|p| p.name == TARGET_PLANET) else {
+            return;
+        };
+        if self.rocket.status != FlightStatus::Flying
+            || self.rocket.primary != target
+            || self.milestones.reached(Milestone::MarsOrbit)
+        {
+            return;
+        }
+        let orbit = self.system.elements(&self.rocket.body, target);
+        let soi = self.system.sphere_of_influence(target, sun);
+        if is_stable(&orbit, &self.system.bodies[target], soi) {
+            let jd = self.current_jd();
+            self.milestones.record(Milestone::MarsOrbit, jd);
+            self.notify(format!("milestone: {} on {}", Milestone::MarsOrbit.name(), format_date(jd)));
+        }
+    }
+
+    // calendar date the simulation has reached
+    fn current_jd(&self) -> f64 {
+        self.start_jd + self.system.time / 86400.0
+    }
+
+    // the departure burn for a transfer picked on the porkchop plot: prograde at the point
+    // of the parking orbit that sends the rocket off along the transfer's excess velocity,
+    // on the pass nearest the departure date
+    fn plan_transfer(&self, transfer: &porkchop::Transfer) -> Result<Maneuver, String> {
+        let not_in_orbit = || format!("get into orbit around {} first", LAUNCH_PLANET);
+        let origin = self
+            .planets
+            .iter()
+            .position(|p| p.name == LAUNCH_PLANET)
+            .ok_or_else(not_in_orbit)?;
+        if self.rocket.status != FlightStatus::Flying || self.rocket.primary != origin {
+            return Err(not_in_orbit());
+        }
+        let planet = self.system.bodies[origin];
+        let orbit = self.system.elements(&self.rocket.body, origin);
+        if !orbit.is_bound() {
+            return Err(not_in_orbit());
+        }
+
+        let now = self.system.time;
+        let at = |time: f64| coast(&self.rocket.body, &planet, time - now).ok_or_else(not_in_orbit);
+        let departure = at(transfer.departure)?;
+        let radius = departure.position - planet.position;
+        let periapsis = escape_periapsis_angle(
+            G * planet.mass,
+            radius.length(),
+            transfer.excess_velocity,
+            orbit.is_retrograde(),
+        );
+        let sense = if orbit.is_retrograde() { -1.0 } else { 1.0 };
+        let ahead = normalize_angle(sense * (periapsis - radius.angle()));
+        let mut time = transfer.departure + ahead / (2.0 * std::f64::consts::PI / orbit.period);
+        if time <= now {
+            time += orbit.period;
         }
+
+        let node = at(time)?;
+        let radius = (node.position - planet.position).length();
+        let speed = (node.velocity - planet.velocity).length();
+        let needed = (transfer.excess_velocity.length_squared() + 2.0 * G * planet.mass / radius).sqrt();
+        Ok(Maneuver {
+            time,
+            prograde: needed - speed,
+            radial: 0.0,
+        })
     }
 }
 
@@ -166,6 +471,10 @@ turbo::go! {
         draw_rocket_screen(&state);
         update_rocket_screen(&mut state);
     }
+    Screen::Porkchop => {
+        porkchop::draw(LAUNCH_PLANET, TARGET_PLANET);
+        update_porkchop_screen(&mut state);
+    }
 }
 
 
@@ -185,74 +494,368 @@ fn draw_game_screen(state: &GameState) {
 }
 
 fn update_game_screen(state: &mut GameState) {
-    // Make a clone of the current state of planets for reading
-    let planets_clone = state.planets.clone();
-
     // Check if rocket has gone too far and reset if necessary
     let max_distance = 20.0 * AU; // 20 times the Earth-Sun distance
-    let distance_from_origin = (state.rocket.x.powi(2) + state.rocket.y.powi(2)).sqrt();
+    let distance_from_origin = state.rocket.body.position.length();
     if distance_from_origin > max_distance {
         log!("Rocket too far, resetting position");
-        state.rocket = Rocket::new(); // Reset the rocket to its initial state
+        state.reset_rocket(); // Reset the rocket to its initial state
     }
-    //state.rocket.update_position();
 
-    // if state.planets.earth.is_within_gravity_range(self.rocket.position()) {
-    //state.rocket.apply_gravity(state.planets[1].clone());
-    //}
-    //log!("Rocket position: {:?}", state.rocket.position());
+    let gp = gamepad(0);
 
-    // Iterate over planets with indices
-    for (i, planet) in state.planets.iter_mut().enumerate() {
-        // Create a slice of all planets except the current one
-        let others = [&planets_clone[..i], &planets_clone[i + 1..]].concat();
+    // time warp: Y faster, X slower, B pause, A single step while paused
+    if gp.y.just_pressed() {
+        state.warp.faster();
+    }
+    if gp.x.just_pressed() {
+        state.warp.slower();
+    }
+    if gp.b.just_pressed() {
+        state.warp.toggle_pause();
+    }
+    if gp.a.just_pressed() {
+        state.warp.request_step();
+    }
 
-        // Update the position of the current planet
-        planet.update_position(&others);
+    // cycle the info panel and the camera through the planets and the rocket
+    if gp.select.just_pressed() {
+        state.selected = (state.selected + 1) % (state.planets.len() + 1);
     }
 
-    let gp = gamepad(0);
+    // mouse wheel zooms around whatever is selected
+    let wheel = mouse(0).wheel[1];
+    if wheel != 0 {
+        state.camera.zoom_by(wheel.signum());
+    }
+
+    let m = mouse(0);
+    let [mx, my] = m.position;
+    // the integrator label steps through the fixed-step integrators and the adaptive one
+    if m.left.just_pressed() && (10..=INTEGRATOR_LABEL_WIDTH).contains(&mx) && (10..20).contains(&my) {
+        state.system.next_integrator();
+    }
+
+    // the porkchop plot label opens the transfer planner
+    if m.left.just_pressed() && (10..=PORKCHOP_LABEL_WIDTH).contains(&mx) && (90..100).contains(&my) {
+        open_porkchop_screen(state);
+    }
+
+    // the autopilot label engages or disengages it, and any manual control takes over
+    if m.left.just_pressed() && (10..=AUTOPILOT_LABEL_WIDTH).contains(&mx) && (100..110).contains(&my) {
+        if state.autopilot.engaged {
+            state.autopilot.disengage();
+        } else {
+            state.autopilot.engage();
+        }
+    }
+    // the arrival label switches between a capture burn and an aerocapture
+    if m.left.just_pressed() && (10..=ARRIVAL_LABEL_WIDTH).contains(&mx) && (110..120).contains(&my) {
+        state.autopilot.aerocapture = !state.autopilot.aerocapture;
+    }
+    let manual = gp.start.just_pressed()
+        || gp.up.pressed()
+        || gp.down.pressed()
+        || gp.left.pressed()
+        || gp.right.pressed()
+        || (gp.a.just_pressed() && !state.warp.paused);
+    if manual && state.autopilot.engaged {
+        state.autopilot.disengage();
+        state.notify("autopilot off, you have control".to_string());
+    }
+
+    // START lights or cuts the engine; a wrecked rocket is replaced instead
+    if gp.start.just_pressed() {
+        if state.rocket.status.is_wrecked() {
+            state.reset_rocket();
+        } else {
+            state.rocket.toggle_engine();
+        }
+    }
 
-    // Checks if the A button is JustPressed
-    if gp.start.pressed() {
-        state.rocket.is_launching = true;
+    // UP/DOWN throttle, LEFT/RIGHT turn the nose (dropping any hold), A cycles the holds
+    // unless it is stepping a paused game
+    if gp.up.pressed() {
+        state.rocket.throttle_by(THROTTLE_RATE);
+    }
+    if gp.down.pressed() {
+        state.rocket.throttle_by(-THROTTLE_RATE);
+    }
+    if gp.right.pressed() {
+        state.rocket.rotate(TURN_RATE);
+    }
+    if gp.left.pressed() {
+        state.rocket.rotate(-TURN_RATE);
+    }
+    if gp.a.just_pressed() && !state.warp.paused {
+        state.rocket.attitude = state.rocket.attitude.next();
+    }
 
-    }else{
-        state.rocket.is_launching = false;
- 
+    // slow down automatically while burning or when the rocket closes in on a body
+    let flying = state.rocket.status == FlightStatus::Flying;
+    let in_air = state.rocket.in_atmosphere(&state.system);
+    let mut max_seconds = if flying {
+        timewarp::encounter_limit(&state.system, &state.rocket.body)
+    } else {
+        f64::INFINITY
+    };
+    if state.rocket.is_firing() {
+        max_seconds = max_seconds.min(timewarp::MAX_BURN_SECONDS);
+    }
+    if in_air {
+        max_seconds = max_seconds.min(timewarp::MAX_AIR_SECONDS);
+    }
+    // and never warp past the start of the next planned burn
+    state.planner.expire(state.system.time, &state.rocket.propulsion);
+    if let Some(until) = state.planner.until_burn(state.system.time, &state.rocket.propulsion) {
+        if until > 0.0 {
+            max_seconds = max_seconds.min(until);
+        } else if state.autopilot.engaged {
+            // the autopilot lights the engine in the coming step
+            max_seconds = max_seconds.min(timewarp::MAX_BURN_SECONDS);
+        }
+    }
+    let dt = state.warp.frame_seconds(max_seconds);
+
+    if dt > 0.0 {
+        // the air changes within seconds, so a frame in it is flown in short substeps
+        let substeps = if in_air {
+            (dt / ATMOSPHERE_STEP_SECONDS).ceil().max(1.0) as usize
+        } else {
+            1
+        };
+        for _ in 0..substeps {
+            state.step(dt / substeps as f64);
+        }
+    }
+    let sun = sun_index(state);
+
+    // where the rocket is heading if it coasts from here
+    let primary = state.rocket.primary;
+    let target = state.planets.iter().position(|p| p.name == TARGET_PLANET);
+    let flying = state.rocket.status == FlightStatus::Flying;
+    if flying {
+        let key = trajectory::PredictionKey {
+            thrusting: state.rocket.is_firing(),
+            warp: state.warp.label(),
+            primary,
+            target,
+            maneuvers: state.planner.maneuvers.clone(),
+        };
+        trajectory::update(&state.system, &state.rocket.body, key, state.prediction_horizon);
+    } else {
+        trajectory::clear();
     }
-/*
-    // Update rocket position and apply launch if necessary
-    if state.rocket.is_launching {
 
-        // Calculate initial launch velocity (adjust these values as needed)
-        let launch_angle = std::f64::consts::PI / 4.0; // 45 degrees
-        let launch_speed = 10000.0; // Adjust this value for desired orbit height
+    let followed = match state.system.bodies.get(state.selected) {
+        Some(body) => body.position,
+        None => state.rocket.body.position,
+    };
+    state.camera.follow(followed);
+    let camera = state.camera;
+    if flying {
+        state.planner.update(&camera, state.system.bodies[primary].position, state.system.time);
+    }
 
-        state.rocket.velocity_x = launch_speed * launch_angle.cos();
-        state.rocket.velocity_y = launch_speed * launch_angle.sin();
+    for (planet, body) in state.planets.iter().zip(&state.system.bodies) {
+        planet.draw(body, &camera);
     }
-*/
-    for planet in &state.planets {
-        let dx = planet.x - state.rocket.x;
-        let dy = planet.y - state.rocket.y;
-        let distance = (dx * dx + dy * dy).sqrt();
-        let force = G * planet.mass / (distance * distance + SOFTENING_FACTOR);
-        let angle = dy.atan2(dx);
-        //state.rocket.velocity_x += force * angle.cos() * TIMESTEP;
-        //state.rocket.velocity_y += force * angle.sin() * TIMESTEP;
+    if flying {
+        trajectory::draw(
+            &camera,
+            state.system.bodies[primary].position,
+            &state.planets[primary].name,
+            target.map_or("", |t| state.planets[t].name.as_str()),
+        );
+        state.planner.draw(&camera, state.system.bodies[primary].position);
     }
 
-    //state.rocket.rotation = state.rocket.velocity_y.atan2(state.rocket.velocity_x);
-    state.rocket.update(&state.planets[..], TIMESTEP);
-    for planet in &state.planets {
-        planet.draw();
+    if !state.rocket.status.is_wrecked() {
+        state.rocket.draw(&camera);
     }
 
-    state.rocket.draw(SCALE);
+    text!(
+        &if state.system.adaptive {
+            format!(
+                "[integrator: dormand-prince 5(4), {} substeps{}, click for fixed steps]",
+                state.system.stepper.last_substeps,
+                match state.system.stepper.behind {
+                    behind if behind > 0.0 => format!(", {} behind", hud::format_duration(behind)),
+                    _ => String::new(),
+                }
+            )
+        } else {
+            format!("[integrator: {}, click for the next]", state.system.integrator.name())
+        },
+        x = 10,
+        y = 10,
+        font = Font::S,
+    );
+    text!(&format!("time warp: {}", state.warp.label()), x = 10, y = 20, font = Font::S);
+    text!(&format!("date: {}", format_date(state.current_jd())), x = 10, y = 30, font = Font::S);
+    text!(
+        &format!(
+            "rocket: {} around {}",
+            state.rocket.propagation.name(),
+            state.planets[state.rocket.primary].name
+        ),
+        x = 10,
+        y = 40,
+        font = Font::S,
+    );
+
+    text!(
+        &format!("zoom: x{:.0} (mouse wheel)", state.camera.zoom),
+        x = 10,
+        y = 50,
+        font = Font::S,
+    );
+
+    text!(
+        &format!(
+            "engine: {} at {:.0}% (START, UP/DOWN)  attitude: {} (A, LEFT/RIGHT)",
+            if state.rocket.engine_on { "on" } else { "off" },
+            state.rocket.throttle * 100.0,
+            state.rocket.attitude.name()
+        ),
+        x = 10,
+        y = 60,
+        font = Font::S,
+    );
+
+    text!("[porkchop plot: click to plan a transfer]", x = 10, y = 90, font = Font::S);
+    text!(
+        &format!(
+            "[{}]",
+            state.autopilot.status(&state.planner, &state.rocket.propulsion, state.system.time)
+        ),
+        x = 10,
+        y = 100,
+        font = Font::S,
+    );
+    text!(
+        if state.autopilot.aerocapture {
+            "[arrival: aerocapture through the air, click for a capture burn]"
+        } else {
+            "[arrival: capture burn, click for an aerocapture]"
+        },
+        x = 10,
+        y = 110,
+        font = Font::S,
+    );
+
+    let wrecked = state.rocket.status.is_wrecked();
+    let recent = state.tick.saturating_sub(state.message_tick) < MESSAGE_FRAMES;
+    if !state.message.is_empty() && (recent || wrecked) {
+        text!(&state.message, x = 10, y = 75, font = Font::M);
+    }
+
+    draw_info_panel(state);
+
+    if SHOW_DEBUG_OVERLAY {
+        draw_debug_overlay(state);
+    }
 
 }
 
+// orbital elements of the selected body around its primary
+fn draw_info_panel(state: &GameState) {
+    let sun = sun_index(state);
+    let (name, body, primary) = match state.planets.get(state.selected) {
+        Some(planet) if planet.sun => (planet.name.as_str(), state.system.bodies[sun], None),
+        Some(planet) => (planet.name.as_str(), state.system.bodies[state.selected], Some(sun)),
+        None => (
+            state.rocket.name(),
+            state.rocket.body,
+            Some(state.rocket.primary),
+        ),
+    };
+    let elements = primary.map(|p| state.system.elements(&body, p));
+    let primary_name = primary.map(|p| state.planets[p].name.as_str());
+    let top = hud::draw_orbit_panel(name, primary_name, elements.as_ref(), 10);
+    let top = hud::draw_propulsion_panel(&state.rocket.propulsion, top);
+    let top = hud::draw_ascent_panel(&state.rocket.ascent, &state.rocket.airframe, top);
+    let top = planner::draw_countdown(&state.planner, &state.rocket.propulsion, state.system.time, top);
+    let top = draw_approach_panel(state, top);
+    let top = draw_transfer_panel(state, top);
+    milestones::draw_milestones_panel(&state.milestones, top);
+}
+
+// the rocket falling towards the target from outside, before its closest approach
+fn draw_approach_panel(state: &GameState, top: i32) -> i32 {
+    let primary = state.rocket.primary;
+    if state.rocket.status != FlightStatus::Flying || state.planets[primary].name != TARGET_PLANET {
+        return top;
+    }
+    let planet = state.system.bodies[primary];
+    let mu = G * planet.mass;
+    let Some(arriving) = approach(&state.system.elements(&state.rocket.body, primary), mu) else {
+        return top;
+    };
+    let burn = capture_burn(mu, &arriving, arriving.periapsis);
+    hud::draw_approach_panel(TARGET_PLANET, &planet, &arriving, burn, top)
+}
+
+// the next hohmann window from the launch planet to the target
+fn draw_transfer_panel(state: &GameState, top: i32) -> i32 {
+    let sun = sun_index(state);
+    let find = |name: &str| state.planets.iter().position(|p| p.name == name);
+    let (Some(origin), Some(target)) = (find(LAUNCH_PLANET), find(TARGET_PLANET)) else {
+        return top;
+    };
+    let Some(window) = launch_window(&state.system, origin, target, sun) else {
+        return top;
+    };
+    let planet = state.system.bodies[origin];
+    let parking_burn = departure_burn(
+        G * planet.mass,
+        planet.radius + PARKING_ALTITUDE,
+        window.transfer.departure_delta_v,
+    );
+    let date = format_date(state.current_jd() + window.wait / 86400.0);
+    hud::draw_transfer_panel(LAUNCH_PLANET, TARGET_PLANET, &window, parking_burn, &date, top)
+}
+
+// conservation diagnostics: anything much above 1e-6 means the integrator or the
+// softening is misbehaving
+fn draw_debug_overlay(state: &GameState) {
+    let conserved = state.system.diagnostics.current;
+    let drift = state.system.drift();
+    let lines = [
+        format!("energy: {:.6e} J (drift {:+.2e})", conserved.energy, drift.energy),
+        format!(
+            "momentum: ({:.3e}, {:.3e}) kg m/s (drift {:+.2e})",
+            conserved.momentum.x, conserved.momentum.y, drift.momentum
+        ),
+        format!(
+            "angular momentum: {:.6e} kg m2/s (drift {:+.2e})",
+            conserved.angular_momentum, drift.angular_momentum
+        ),
+        format!("simulated time: {:.1} days", state.system.time / 86400.0),
+    ];
+    let [_, screen_h] = canvas_size!();
+    let top = screen_h as i32 - 10 * lines.len() as i32 - 10;
+    for (i, line) in lines.iter().enumerate() {
+        text!(line, x = 10, y = top + 10 * i as i32, font = Font::S);
+    }
+}
+
+// index of the body the planets measure their distance to
+fn sun_index(state: &GameState) -> usize {
+    state.planets.iter().position(|p| p.sun).unwrap_or(0)
+}
+
+// a fresh flight of the catalog rocket called `name` (the first one if there is no such
+// rocket) carrying the mission payload
+fn spawn_rocket(name: &str) -> Result<RocketInstance, String> {
+    let rockets = catalog::builtin().map_err(|err| err.to_string())?;
+    let spec = rockets
+        .get(name)
+        .or_else(|| rockets.all().first())
+        .ok_or_else(|| "the rocket catalog is empty".to_string())?;
+    Ok(RocketInstance::from_spec(spec, MISSION_PAYLOAD))
+}
+
 fn draw_title_screen(state: &GameState) {
     let [screen_w, screen_h] = canvas_size!();
     //log!("DRAW_TITLE_SCREEN");
@@ -282,6 +885,13 @@ fn draw_title_screen(state: &GameState) {
     if state.tick % 60 < 30 {
         text!("PRESS START", font = Font::L, x = x, y = y);
     }
+    let date = format!("LAUNCH DATE {}", format_date(state.start_jd));
+    text!(&date, font = Font::M, x = x - 16, y = y + 20);
+    text!("LEFT/RIGHT MONTH  UP/DOWN YEAR  X/Y DAY", font = Font::S, x = x - 56, y = y + 34);
+    let propagation = format!("B COASTING: {}", state.propagation.name().to_uppercase());
+    text!(&propagation, font = Font::S, x = x - 56, y = y + 44);
+    let site = format!("SELECT LAUNCH SITE: {} ON {}", LAUNCH_SITES[state.launch_site].0, LAUNCH_PLANET);
+    text!(&site.to_uppercase(), font = Font::S, x = x - 56, y = y + 54);
     // Show players who joined
     /*
     let num_players = state.players.len();
@@ -293,7 +903,46 @@ fn draw_title_screen(state: &GameState) {
 }
 
 fn update_title_screen(state: &mut GameState) {
-    if gamepad(0).start.just_pressed() || gamepad(0).a.just_pressed() {
+    let gp = gamepad(0);
+
+    // pick the date the planets start at
+    let (year, month, day) = calendar_date(state.start_jd);
+    let mut months = year * 12 + month as i32 - 1;
+    if gp.right.just_pressed() {
+        months += 1;
+    }
+    if gp.left.just_pressed() {
+        months -= 1;
+    }
+    if gp.up.just_pressed() {
+        months += 12;
+    }
+    if gp.down.just_pressed() {
+        months -= 12;
+    }
+    if months != year * 12 + month as i32 - 1 {
+        // the element table covers 1800-2050; keep the day valid in every month
+        let months = months.clamp(1800 * 12, 2050 * 12 + 11);
+        let month = months.rem_euclid(12) as u32 + 1;
+        state.start_jd = julian_date(months.div_euclid(12), month, day.min(28));
+    }
+    if gp.y.just_pressed() {
+        state.start_jd += 1.0;
+    }
+    if gp.x.just_pressed() {
+        state.start_jd -= 1.0;
+    }
+
+    // analytic coasting inside each sphere of influence, or numerical integration
+    if gp.b.just_pressed() {
+        state.propagation = state.propagation.toggle();
+    }
+    if gp.select.just_pressed() {
+        state.launch_site = (state.launch_site + 1) % LAUNCH_SITES.len();
+    }
+
+    if gp.start.just_pressed() || gp.a.just_pressed() {
+        state.start_run();
         state.screen = Screen::Game;
         state.tick = 0;
     }
@@ -312,6 +961,42 @@ fn update_title_screen(state: &mut GameState) {
     */
 }
 
+// transfers from the launch planet to the target over the next synodic period or so
+fn open_porkchop_screen(state: &mut GameState) {
+    let sun = sun_index(state);
+    let find = |name: &str| state.planets.iter().position(|p| p.name == name);
+    let (Some(origin), Some(target)) = (find(LAUNCH_PLANET), find(TARGET_PLANET)) else {
+        return;
+    };
+    porkchop::compute(&state.system, origin, target, sun, state.start_jd);
+    state.screen = Screen::Porkchop;
+}
+
+// B or RIGHT click goes back, LEFT click on a transfer plans its departure burn
+fn update_porkchop_screen(state: &mut GameState) {
+    let picked = porkchop::clicked();
+    let back = gamepad(0).b.just_pressed() || mouse(0).right.just_pressed();
+    if picked.is_none() && !back {
+        return;
+    }
+    if let Some(transfer) = picked {
+        match state.plan_transfer(&transfer) {
+            Ok(maneuver) => {
+                state.planner.add(maneuver);
+                state.autopilot.follow(transfer.arrival);
+                state.notify(format!(
+                    "departure burn of {:.0} m/s planned, arriving {}",
+                    maneuver.prograde,
+                    format_date(state.start_jd + transfer.arrival / 86400.0)
+                ));
+            }
+            Err(err) => state.notify(err),
+        }
+    }
+    porkchop::clear();
+    state.screen = Screen::Game;
+}
+
 fn draw_rocket_screen(state: &GameState) {
     // Make a clone of the current state of planets for reading
 }
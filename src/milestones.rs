@@ -0,0 +1,55 @@
+// mission milestones: what the player has achieved this run, and on which date
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::hud::draw_panel;
+use crate::physics::ephemeris::format_date;
+
+#[derive(Clone, Copy, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum Milestone {
+    // bound, clear of the air and inside the sphere of influence
+    MarsOrbit,
+}
+
+impl Milestone {
+    pub fn name(self) -> &'static str {
+        match self {
+            Milestone::MarsOrbit => "stable orbit around Mars",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Milestones {
+    reached: Vec<(Milestone, f64)>, // with the julian date, in the order they were reached
+}
+
+impl Milestones {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reached(&self, milestone: Milestone) -> bool {
+        self.reached.iter().any(|&(m, _)| m == milestone)
+    }
+
+    // note `milestone` as reached on `jd`; false if it already was
+    pub fn record(&mut self, milestone: Milestone, jd: f64) -> bool {
+        if self.reached(milestone) {
+            return false;
+        }
+        self.reached.push((milestone, jd));
+        true
+    }
+}
+
+// the milestones reached so far, nothing until there is one
+pub fn draw_milestones_panel(milestones: &Milestones, top: i32) -> i32 {
+    if milestones.reached.is_empty() {
+        return top;
+    }
+    let mut lines = vec!["milestones:".to_string()];
+    for &(milestone, jd) in &milestones.reached {
+        lines.push(format!("{} - {}", format_date(jd), milestone.name()));
+    }
+    draw_panel(&lines, top)
+}
@@ -0,0 +1,249 @@
+// embedded dormand-prince 5(4) stepper with error control, so the step shrinks
+// during close encounters and grows again in cruise
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{Body, Vec2, G};
+
+// butcher tableau of the dormand-prince pair (the time column is unused because
+// gravity does not depend on time)
+const A: [&[f64]; 6] = [
+    &[1.0 / 5.0],
+    &[3.0 / 40.0, 9.0 / 40.0],
+    &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+    &[19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0],
+    &[9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0],
+    &[35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0],
+];
+
+// 5th order weights (first same as last: equal to the final row of A)
+const B: [f64; 7] = [
+    35.0 / 384.0,
+    0.0,
+    500.0 / 1113.0,
+    125.0 / 192.0,
+    -2187.0 / 6784.0,
+    11.0 / 84.0,
+    0.0,
+];
+
+// difference between the 5th and 4th order weights, used as the error estimate
+const E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339_200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+// safety margins for the step size controller
+const SAFETY: f64 = 0.9;
+const MIN_FACTOR: f64 = 0.2;
+const MAX_FACTOR: f64 = 5.0;
+
+// never spend more than this many substeps on a single call to `advance`
+const MAX_SUBSTEPS: usize = 20_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct AdaptiveStepper {
+    pub tolerance: f64, // allowed error per step, relative to each body's local scale
+    pub min_dt: f64,    // seconds
+    pub max_dt: f64,    // seconds
+    pub next_dt: f64,   // step size the controller will try next
+    pub last_substeps: usize, // substeps taken by the last call to `advance`
+    pub behind: f64,          // seconds the substep budget ran out on, made up in the next call
+}
+
+impl Default for AdaptiveStepper {
+    fn default() -> Self {
+        Self {
+            tolerance: 1.0e-9,
+            min_dt: 1.0e-3,
+            max_dt: 3600.0 * 24.0 * 7.0,
+            next_dt: 60.0,
+            last_substeps: 0,
+            behind: 0.0,
+        }
+    }
+}
+
+impl AdaptiveStepper {
+    // advance `bodies` by `duration` seconds (plus whatever an earlier call fell behind
+    // by) using as many substeps as the error control requires. a step is only taken
+    // when its error is finite; time the substep budget does not cover is left in
+    // `behind` instead of being flown in one unchecked step.
+    pub fn advance<F>(&mut self, bodies: &mut [Body], duration: f64, accelerations: F)
+    where
+        F: Fn(&[Body]) -> Vec<Vec2>,
+    {
+        let mut remaining = duration + self.behind;
+        let mut substeps = 0;
+
+        while remaining > 0.0 && substeps < MAX_SUBSTEPS {
+            let planned = self.next_dt.clamp(self.min_dt, self.max_dt);
+            let dt = planned.min(remaining);
+            let (candidate, error) = dormand_prince(bodies, dt, &accelerations);
+            let error = error / self.tolerance;
+
+            // accept when the error is within tolerance, or when the step cannot shrink any
+            // further; never a step that blew up
+            let accepted = error.is_finite() && (error <= 1.0 || dt <= self.min_dt);
+            if accepted {
+                bodies.copy_from_slice(&candidate);
+                remaining -= dt;
+            }
+
+            let factor = if !error.is_finite() {
+                MIN_FACTOR
+            } else if error > 0.0 {
+                (SAFETY * error.powf(-0.2)).clamp(MIN_FACTOR, MAX_FACTOR)
+            } else {
+                MAX_FACTOR
+            };
+
+            // a step clipped to the end of the interval says little about the next one
+            let clipped = dt < planned;
+            self.next_dt = if accepted && clipped {
+                planned.max(dt * factor)
+            } else {
+                dt * factor
+            };
+            substeps += 1;
+        }
+
+        self.behind = remaining.max(0.0);
+        self.last_substeps = substeps;
+    }
+}
+
+// one dormand-prince step, returning the 5th order result and the scaled error estimate
+fn dormand_prince<F>(start: &[Body], dt: f64, accelerations: &F) -> (Vec<Body>, f64)
+where
+    F: Fn(&[Body]) -> Vec<Vec2>,
+{
+    // derivative of each stage: (velocity, acceleration) per body
+    let mut stages: Vec<Vec<(Vec2, Vec2)>> = Vec::with_capacity(7);
+    stages.push(derivative(start, accelerations));
+
+    for row in A {
+        let state = combine(start, &stages, row, dt);
+        stages.push(derivative(&state, accelerations));
+    }
+
+    let result = combine(start, &stages, &B, dt);
+    let scales = error_scales(start);
+
+    let mut error: f64 = 0.0;
+    for (i, &(position_scale, velocity_scale)) in scales.iter().enumerate() {
+        let mut position_error = Vec2::ZERO;
+        let mut velocity_error = Vec2::ZERO;
+        for (stage, &weight) in stages.iter().zip(&E) {
+            position_error += stage[i].0 * (weight * dt);
+            velocity_error += stage[i].1 * (weight * dt);
+        }
+        error = error
+            .max(position_error.length() / position_scale)
+            .max(velocity_error.length() / velocity_scale);
+    }
+    // `max` skips NaN, so a step that blew up has to be flagged explicitly
+    if result.iter().any(|b| !b.position.is_finite() || !b.velocity.is_finite()) {
+        error = f64::INFINITY;
+    }
+    (result, error)
+}
+
+fn derivative<F>(bodies: &[Body], accelerations: &F) -> Vec<(Vec2, Vec2)>
+where
+    F: Fn(&[Body]) -> Vec<Vec2>,
+{
+    bodies
+        .iter()
+        .zip(accelerations(bodies))
+        .map(|(body, acceleration)| (body.velocity, acceleration))
+        .collect()
+}
+
+// start + dt * sum(weights[j] * stages[j])
+fn combine(start: &[Body], stages: &[Vec<(Vec2, Vec2)>], weights: &[f64], dt: f64) -> Vec<Body> {
+    start
+        .iter()
+        .enumerate()
+        .map(|(i, body)| {
+            let mut next = *body;
+            for (stage, &weight) in stages.iter().zip(weights) {
+                if weight != 0.0 {
+                    next.position += stage[i].0 * (weight * dt);
+                    next.velocity += stage[i].1 * (weight * dt);
+                }
+            }
+            next
+        })
+        .collect()
+}
+
+// the length and speed each body's error is measured against: its distance to and
+// orbital speed around the nearest other body, so a rocket skimming Earth is held
+// to meters while Neptune is held to thousands of kilometers
+fn error_scales(bodies: &[Body]) -> Vec<(f64, f64)> {
+    bodies
+        .iter()
+        .enumerate()
+        .map(|(i, body)| {
+            let nearest = bodies
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| j != i && other.mass > 0.0)
+                .map(|(_, other)| other)
+                .min_by(|a, b| body.distance_to(a).total_cmp(&body.distance_to(b)));
+
+            match nearest {
+                Some(other) => {
+                    let distance = body.distance_to(other).max(1.0);
+                    let relative_speed = (body.velocity - other.velocity).length();
+                    let orbital_speed = (G * (body.mass + other.mass) / distance).sqrt();
+                    (distance, relative_speed.max(orbital_speed).max(1.0e-3))
+                }
+                None => (
+                    body.position.length().max(1.0),
+                    body.velocity.length().max(1.0),
+                ),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drifting() -> Vec<Body> {
+        vec![Body::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), 1.0)]
+    }
+
+    #[test]
+    fn blown_up_steps_are_never_taken() {
+        let mut stepper = AdaptiveStepper::default();
+        let mut bodies = drifting();
+        stepper.advance(&mut bodies, 10.0, |b| vec![Vec2::new(f64::NAN, 0.0); b.len()]);
+        assert_eq!(bodies[0].position, Vec2::new(0.0, 0.0));
+        assert_eq!(stepper.behind, 10.0);
+    }
+
+    #[test]
+    fn time_past_the_budget_is_carried_over() {
+        let mut stepper = AdaptiveStepper {
+            max_dt: 1.0,
+            ..Default::default()
+        };
+        let mut bodies = drifting();
+        let duration = MAX_SUBSTEPS as f64 + 500.0;
+        stepper.advance(&mut bodies, duration, |b| vec![Vec2::new(0.0, 0.0); b.len()]);
+        assert_eq!(stepper.last_substeps, MAX_SUBSTEPS);
+        assert!((stepper.behind - 500.0).abs() < 1.0e-6);
+
+        stepper.advance(&mut bodies, 0.0, |b| vec![Vec2::new(0.0, 0.0); b.len()]);
+        assert_eq!(stepper.behind, 0.0);
+        assert!((bodies[0].position.x - duration).abs() < 1.0e-6);
+    }
+}
@@ -0,0 +1,68 @@
+// air around a body: an exponential density profile turning with the ground, and the
+// drag, dynamic pressure and heating it puts on a craft flying through it
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{Body, Vec2};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Atmosphere {
+    pub surface_density: f64, // kg/m^3 at zero altitude
+    pub scale_height: f64,    // m over which the density drops by a factor of e
+    pub height: f64,          // m above the surface where the model ends, no air above
+    pub heating: f64,         // sutton-graves constant of the gas, see `heat_flux`
+}
+
+impl Atmosphere {
+    pub fn new(surface_density: f64, scale_height: f64, height: f64, heating: f64) -> Self {
+        Self {
+            surface_density,
+            scale_height,
+            height,
+            heating,
+        }
+    }
+
+    // kg/m^3 at `altitude` meters above the surface
+    pub fn density(&self, altitude: f64) -> f64 {
+        if altitude >= self.height {
+            return 0.0;
+        }
+        self.surface_density * (-altitude.max(0.0) / self.scale_height).exp()
+    }
+}
+
+impl Body {
+    // density of this body's air at `at` and the velocity the air has there, carried
+    // around with the ground; `None` above the atmosphere or for a body without one
+    pub fn air_at(&self, at: Vec2) -> Option<(f64, Vec2)> {
+        let atmosphere = self.atmosphere?;
+        let offset = at - self.position;
+        let density = atmosphere.density(offset.length() - self.radius);
+        (density > 0.0).then(|| (density, self.surface_velocity(offset)))
+    }
+}
+
+// pascal, 1/2 rho v^2
+pub fn dynamic_pressure(density: f64, speed: f64) -> f64 {
+    0.5 * density * speed * speed
+}
+
+// W/m^2 of convective heating at the stagnation point of a craft with a nose of
+// `nose_radius` m moving at `speed` through air of `density` (sutton-graves,
+// k sqrt(rho / r) v^3, with `heating` the constant k of the gas)
+pub fn heat_flux(density: f64, speed: f64, nose_radius: f64, heating: f64) -> f64 {
+    if nose_radius <= 0.0 {
+        return 0.0;
+    }
+    heating * (density / nose_radius).sqrt() * speed.powi(3)
+}
+
+// acceleration of a craft of `mass` kg with frontal `area` m^2 moving at `air_velocity`
+// through air of `density`, pointing against the motion
+pub fn drag_acceleration(density: f64, air_velocity: Vec2, drag_coefficient: f64, area: f64, mass: f64) -> Vec2 {
+    if mass <= 0.0 {
+        return Vec2::ZERO;
+    }
+    let force = dynamic_pressure(density, air_velocity.length()) * drag_coefficient * area;
+    -air_velocity.normalized() * (force / mass)
+}
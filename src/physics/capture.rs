@@ -0,0 +1,221 @@
+// arriving at a planet: the hyperbola a craft comes in on, the burns that turn it into
+// an orbit and the single pass through the air that does the same for free (aerocapture)
+use std::f64::consts::PI;
+
+use super::{Body, OrbitalElements, Vec2, G};
+
+// fixed step of the simulated aerocapture pass, in seconds
+const PASS_STEP: f64 = 0.2;
+
+// a pass still in the air after this many orbits at the top of it is decaying into the ground
+const PASS_ORBITS: f64 = 3.0;
+
+const BISECTIONS: usize = 40;
+
+// a craft falling towards a planet from outside, before its closest approach
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Approach {
+    pub periapsis: f64,         // m from the planet's center
+    pub excess_speed: f64,      // m/s far away (v infinity)
+    pub periapsis_speed: f64,   // m/s at the closest approach
+    pub time_to_periapsis: f64, // s
+}
+
+// the approach `orbit` (around a body with gravitational parameter `mu`) describes;
+// `None` once the craft is bound or past periapsis
+pub fn approach(orbit: &OrbitalElements, mu: f64) -> Option<Approach> {
+    if orbit.is_bound() {
+        return None;
+    }
+    let time_to_periapsis = orbit.time_to_periapsis(mu);
+    if time_to_periapsis <= 0.0 {
+        return None;
+    }
+    let excess_speed = (-mu / orbit.semi_major_axis).sqrt();
+    Some(Approach {
+        periapsis: orbit.periapsis,
+        excess_speed,
+        periapsis_speed: (excess_speed * excess_speed + 2.0 * mu / orbit.periapsis).sqrt(),
+        time_to_periapsis,
+    })
+}
+
+// prograde m/s (negative, a retrograde burn) at the approach's periapsis that leaves an
+// orbit reaching out to `apoapsis` m from the center; equal to the periapsis for a circle
+pub fn capture_burn(mu: f64, approach: &Approach, apoapsis: f64) -> f64 {
+    let a = (approach.periapsis + apoapsis.max(approach.periapsis)) / 2.0;
+    (mu * (2.0 / approach.periapsis - 1.0 / a)).sqrt() - approach.periapsis_speed
+}
+
+// prograde m/s at apoapsis that lifts the periapsis of a bound `orbit` to `periapsis` m
+// from the center, to climb out of the air after an aerocapture
+pub fn periapsis_raise(mu: f64, orbit: &OrbitalElements, periapsis: f64) -> f64 {
+    let apoapsis = orbit.apoapsis;
+    let speed = |a: f64| (mu * (2.0 / apoapsis - 1.0 / a)).sqrt();
+    speed((apoapsis + periapsis) / 2.0) - speed(orbit.semi_major_axis)
+}
+
+// velocity change across the radius at `position` (moving at `velocity` around a body
+// with `mu`) that moves the periapsis to `periapsis` m from the center. more speed
+// across the radius is more angular momentum and a higher periapsis.
+pub fn periapsis_trim(mu: f64, position: Vec2, velocity: Vec2, periapsis: f64) -> Option<Vec2> {
+    let mut across = position.normalized().perp();
+    if across.dot(velocity) < 0.0 {
+        across = -across;
+    }
+    let speed_across = across.dot(velocity);
+    let periapsis_after = |change: f64| OrbitalElements::from_state(position, velocity + across * change, mu).periapsis;
+
+    let (mut low, mut high) = (-0.999 * speed_across, speed_across.max(1.0));
+    if periapsis_after(low) > periapsis || periapsis_after(high) < periapsis {
+        return None;
+    }
+    for _ in 0..BISECTIONS {
+        let middle = (low + high) / 2.0;
+        if periapsis_after(middle) < periapsis {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(across * ((low + high) / 2.0))
+}
+
+// bound, clear of the air and the ground, and inside `sphere_of_influence`
+pub fn is_stable(orbit: &OrbitalElements, planet: &Body, sphere_of_influence: f64) -> bool {
+    let air = planet.atmosphere.map_or(0.0, |air| air.height);
+    orbit.is_bound() && orbit.periapsis > planet.radius + air && orbit.apoapsis < sphere_of_influence
+}
+
+// periapsis (m from the center) for a craft arriving at `planet` with `excess_speed`
+// that one pass through the air turns into an orbit reaching out to `apoapsis`.
+// `ballistic` is drag coefficient times area over mass. the pass is flown with a fixed
+// step and still air, so it is a first guess for the corrections; `None` when even the
+// lowest pass does not slow the craft down enough, the planet has no air or the
+// craft is not arriving from outside (no positive `excess_speed`).
+pub fn aerocapture_periapsis(planet: &Body, excess_speed: f64, ballistic: f64, apoapsis: f64) -> Option<f64> {
+    let air = planet.atmosphere?;
+    if !(excess_speed.is_finite() && excess_speed > 0.0) {
+        return None;
+    }
+    let mu = G * planet.mass;
+    let top = planet.radius + air.height;
+    let max_steps = (PASS_ORBITS * 2.0 * PI * (top.powi(3) / mu).sqrt() / PASS_STEP) as usize;
+    // energy left after the pass, `None` if the craft hits the ground or stays in the air
+    let pass = |periapsis: f64| -> Option<f64> {
+        let a = -mu / (excess_speed * excess_speed);
+        let e = 1.0 - periapsis / a;
+        let p = a * (1.0 - e * e);
+        // start inbound at the top of the air
+        let anomaly = -((p / top - 1.0) / e).clamp(-1.0, 1.0).acos();
+        let mut position = Vec2::from_angle(anomaly) * top;
+        let mut velocity = Vec2::new(-anomaly.sin(), e + anomaly.cos()) * (mu / p).sqrt();
+        for _ in 0..max_steps {
+            let altitude = position.length() - planet.radius;
+            if altitude <= 0.0 {
+                return None;
+            }
+            if altitude > air.height && position.dot(velocity) > 0.0 {
+                return Some(velocity.length_squared() / 2.0 - mu / position.length());
+            }
+            let speed = velocity.length();
+            let drag = velocity * (-0.5 * air.density(altitude) * speed * ballistic);
+            let gravity = position * (-mu / position.length().powi(3));
+            velocity += (gravity + drag) * PASS_STEP;
+            position += velocity * PASS_STEP;
+        }
+        None
+    };
+
+    // lower passes lose more energy; aim for the orbit's energy
+    let (mut low, mut high) = (planet.radius, top);
+    let wanted = -mu / (high + apoapsis);
+    if pass(high).is_some_and(|energy| energy < wanted) {
+        return Some(high);
+    }
+    for _ in 0..BISECTIONS {
+        let middle = (low + high) / 2.0;
+        match pass(middle) {
+            Some(energy) if energy > wanted => high = middle,
+            _ => low = middle,
+        }
+    }
+    // the lowest pass that survives may still not be enough
+    pass(high).filter(|&energy| energy < 0.0).map(|_| high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::Atmosphere;
+
+    fn mars() -> Body {
+        let mut mars = Body::new(Vec2::ZERO, Vec2::ZERO, 6.39e23);
+        mars.radius = 3.3895e6;
+        mars.atmosphere = Some(Atmosphere::new(0.02, 11.1e3, 125.0e3, 1.9027e-4));
+        mars
+    }
+
+    // the hyperbola through `periapsis` with `excess_speed`, an hour before periapsis
+    fn arriving(mu: f64, periapsis: f64, excess_speed: f64) -> OrbitalElements {
+        let a = -mu / (excess_speed * excess_speed);
+        let speed = (excess_speed * excess_speed + 2.0 * mu / periapsis).sqrt();
+        let at_periapsis = OrbitalElements::from_state(Vec2::new(periapsis, 0.0), Vec2::new(0.0, speed), mu);
+        let before = at_periapsis.propagate(-3600.0, mu);
+        assert!((before.semi_major_axis / a - 1.0).abs() < 1.0e-9);
+        before
+    }
+
+    #[test]
+    fn capture_burn_circularizes_at_periapsis() {
+        let mars = mars();
+        let mu = G * mars.mass;
+        let periapsis = mars.radius + 300.0e3;
+        let approach = approach(&arriving(mu, periapsis, 2650.0), mu).unwrap();
+        assert!((approach.periapsis / periapsis - 1.0).abs() < 1.0e-9);
+        assert!((approach.time_to_periapsis - 3600.0).abs() < 1.0e-3);
+
+        let burn = capture_burn(mu, &approach, approach.periapsis);
+        let circular = (mu / periapsis).sqrt();
+        assert!((approach.periapsis_speed + burn - circular).abs() < 1.0e-6);
+        // a higher apoapsis costs less
+        assert!(capture_burn(mu, &approach, 30.0e6) > burn);
+    }
+
+    #[test]
+    fn periapsis_raise_lifts_out_of_the_air() {
+        let mars = mars();
+        let mu = G * mars.mass;
+        let (periapsis, apoapsis) = (mars.radius + 40.0e3, mars.radius + 30.0e6);
+        let a = (periapsis + apoapsis) / 2.0;
+        let speed = (mu * (2.0 / periapsis - 1.0 / a)).sqrt();
+        let orbit = OrbitalElements::from_state(Vec2::new(periapsis, 0.0), Vec2::new(0.0, speed), mu);
+        let raise = periapsis_raise(mu, &orbit, mars.radius + 300.0e3);
+        let (position, velocity) = orbit.state_at(PI, mu);
+        let raised = OrbitalElements::from_state(position, velocity + velocity.normalized() * raise, mu);
+        assert!((raised.periapsis - mars.radius - 300.0e3).abs() < 1.0);
+        assert!(is_stable(&raised, &mars, 5.7e8));
+        assert!(!is_stable(&orbit, &mars, 5.7e8));
+    }
+
+    #[test]
+    fn aerocapture_periapsis_is_inside_the_air() {
+        let mars = mars();
+        let ballistic = 0.3 * 10.75 / 6000.0;
+        let periapsis = aerocapture_periapsis(&mars, 2650.0, ballistic, 30.0e6).unwrap();
+        let altitude = periapsis - mars.radius;
+        assert!(altitude > 20.0e3 && altitude < 60.0e3);
+        // a deeper dive is needed for a tighter orbit
+        let tighter = aerocapture_periapsis(&mars, 2650.0, ballistic, 5.0e6).unwrap();
+        assert!(tighter < periapsis);
+    }
+
+    #[test]
+    fn aerocapture_needs_air_and_an_approach() {
+        let mars = mars();
+        let ballistic = 0.3 * 10.75 / 6000.0;
+        assert_eq!(aerocapture_periapsis(&Body { atmosphere: None, ..mars }, 2650.0, ballistic, 30.0e6), None);
+        assert_eq!(aerocapture_periapsis(&mars, 0.0, ballistic, 30.0e6), None);
+        assert_eq!(aerocapture_periapsis(&mars, f64::NAN, ballistic, 30.0e6), None);
+    }
+}
@@ -0,0 +1,218 @@
+// contact between the craft and bodies, and merging of bodies that run into each other
+use super::{Body, System, Vec2};
+
+// touching down slower than this, relative to the spinning ground, counts as a landing (m/s)
+pub const LANDING_SPEED: f64 = 10.0;
+
+// slack on the surface test (m): a craft standing on the ground at 1 au from the origin
+// is only placed to within rounding error, and must not collide on lift-off
+const SURFACE_TOLERANCE: f64 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CollisionEvent {
+    // the craft reached the surface gently; `offset` is the touchdown point from the body's center
+    Landing { body: usize, speed: f64, offset: Vec2 },
+    Crash { body: usize, speed: f64 },
+    // `absorbed` was removed from the system and its mass added to `survivor`
+    // (indices are from before the removal)
+    Merge { survivor: usize, absorbed: usize },
+}
+
+// check whether the craft reached the surface of a body between two states. the path is
+// taken relative to each body, so a fast craft cannot tunnel through a planet in one
+// frame; a craft that was already inside a body does not collide with it again. the
+// touchdown speed is taken where the path first crosses the surface, not at the end of
+// the step, which may be well below ground.
+pub fn craft_contact(
+    bodies_before: &[Body],
+    bodies_after: &[Body],
+    craft_before: &Body,
+    craft_after: &Body,
+) -> Option<CollisionEvent> {
+    bodies_before
+        .iter()
+        .zip(bodies_after)
+        .enumerate()
+        .filter(|(_, (before, _))| before.radius > 0.0)
+        .filter_map(|(i, (before, after))| {
+            let start = craft_before.position - before.position;
+            let end = craft_after.position - after.position;
+            if start.length() < before.radius - SURFACE_TOLERANCE {
+                return None;
+            }
+            let t = first_crossing(start, end, after.radius - SURFACE_TOLERANCE)?;
+
+            let offset = (start + (end - start) * t).normalized() * after.radius;
+            let velocity = craft_before.velocity + (craft_after.velocity - craft_before.velocity) * t;
+            let ground = Body {
+                velocity: before.velocity + (after.velocity - before.velocity) * t,
+                ..*after
+            };
+            let speed = (velocity - ground.surface_velocity(offset)).length();
+            Some((t, classify(i, speed, offset)))
+        })
+        // the body whose surface the path crosses first is the one hit
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, event)| event)
+}
+
+fn classify(body: usize, speed: f64, offset: Vec2) -> CollisionEvent {
+    if speed <= LANDING_SPEED {
+        CollisionEvent::Landing { body, speed, offset }
+    } else {
+        CollisionEvent::Crash { body, speed }
+    }
+}
+
+// fraction of the segment start..end at which it first enters the circle of `radius`
+// around the origin, if it does
+fn first_crossing(start: Vec2, end: Vec2, radius: f64) -> Option<f64> {
+    let segment = end - start;
+    let a = segment.length_squared();
+    let b = start.dot(segment);
+    let c = start.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    let discriminant = b * b - a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    (0.0..=1.0).contains(&t).then_some(t)
+}
+
+// merge every pair of bodies whose surfaces overlap. momentum and mass are conserved,
+// the merged body sits at the pair's center of mass with the combined volume and keeps
+// the spin and atmosphere of the heavier one. the kinetic energy lost in the impact
+// makes the energy drift meaningless, so the diagnostics are restarted after a merge.
+pub fn merge_overlapping(system: &mut System) -> Vec<CollisionEvent> {
+    let mut events = Vec::new();
+
+    while let Some((a, b)) = overlapping_pair(&system.bodies) {
+        let (survivor, absorbed) = if system.bodies[a].mass >= system.bodies[b].mass {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let gone = system.bodies[absorbed];
+        let kept = &mut system.bodies[survivor];
+
+        let mass = kept.mass + gone.mass;
+        kept.position = (kept.position * kept.mass + gone.position * gone.mass) / mass;
+        kept.velocity = (kept.velocity * kept.mass + gone.velocity * gone.mass) / mass;
+        kept.radius = (kept.radius.powi(3) + gone.radius.powi(3)).cbrt();
+        kept.mass = mass;
+
+        system.bodies.remove(absorbed);
+        events.push(CollisionEvent::Merge { survivor, absorbed });
+    }
+
+    if !events.is_empty() {
+        system.reset_diagnostics();
+    }
+    events
+}
+
+fn overlapping_pair(bodies: &[Body]) -> Option<(usize, usize)> {
+    for (i, a) in bodies.iter().enumerate() {
+        for (j, b) in bodies.iter().enumerate().skip(i + 1) {
+            if a.radius > 0.0 && b.radius > 0.0 && a.distance_to(b) < a.radius + b.radius {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RADIUS: f64 = 6.371e6;
+
+    fn planet(spin: f64) -> Body {
+        Body {
+            radius: RADIUS,
+            spin,
+            ..Body::new(Vec2::ZERO, Vec2::ZERO, 5.9742e24)
+        }
+    }
+
+    // a craft dropping straight down through the surface at `speed`
+    fn touchdown(ground: Body, speed: f64) -> Option<CollisionEvent> {
+        let before = Body::new(Vec2::new(0.0, RADIUS + 5.0), Vec2::new(0.0, -speed), 0.0);
+        let after = Body::new(Vec2::new(0.0, RADIUS - 5.0), Vec2::new(0.0, -speed), 0.0);
+        craft_contact(&[ground], &[ground], &before, &after)
+    }
+
+    #[test]
+    fn landing_speed_splits_landings_from_crashes() {
+        match touchdown(planet(0.0), LANDING_SPEED - 0.1) {
+            Some(CollisionEvent::Landing { body: 0, offset, .. }) => {
+                assert!((offset - Vec2::new(0.0, RADIUS)).length() < 1.0);
+            }
+            other => panic!("expected a landing, got {other:?}"),
+        }
+        assert!(matches!(
+            touchdown(planet(0.0), LANDING_SPEED + 0.1),
+            Some(CollisionEvent::Crash { body: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn touchdown_speed_is_relative_to_the_spinning_ground() {
+        // a gentle descent onto ground sweeping sideways at 465 m/s is a crash
+        let spin = 465.0 / RADIUS;
+        assert!(matches!(touchdown(planet(spin), 1.0), Some(CollisionEvent::Crash { .. })));
+    }
+
+    #[test]
+    fn fast_craft_cannot_tunnel_through() {
+        let ground = planet(0.0);
+        let before = Body::new(Vec2::new(0.0, 2.0 * RADIUS), Vec2::new(0.0, -4.0 * RADIUS), 0.0);
+        let after = Body::new(Vec2::new(0.0, -2.0 * RADIUS), Vec2::new(0.0, -4.0 * RADIUS), 0.0);
+        assert!(matches!(
+            craft_contact(&[ground], &[ground], &before, &after),
+            Some(CollisionEvent::Crash { body: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn craft_already_inside_does_not_collide_again() {
+        let ground = planet(0.0);
+        let before = Body::new(Vec2::new(0.0, RADIUS / 2.0), Vec2::ZERO, 0.0);
+        let after = Body::new(Vec2::new(0.0, RADIUS / 3.0), Vec2::ZERO, 0.0);
+        assert_eq!(craft_contact(&[ground], &[ground], &before, &after), None);
+    }
+
+    #[test]
+    fn merging_conserves_mass_and_momentum() {
+        let heavy = Body {
+            radius: 2.0e6,
+            ..Body::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), 6.0e24)
+        };
+        let light = Body {
+            radius: 1.0e6,
+            ..Body::new(Vec2::new(2.5e6, 0.0), Vec2::new(-500.0, 300.0), 2.0e24)
+        };
+        let apart = Body {
+            radius: 1.0e6,
+            ..Body::new(Vec2::new(1.0e9, 0.0), Vec2::ZERO, 1.0e22)
+        };
+        let mut system = System::new(vec![light, heavy, apart]);
+        let momentum = |bodies: &[Body]| bodies.iter().map(|b| b.velocity * b.mass).fold(Vec2::ZERO, |a, b| a + b);
+        let mass = |bodies: &[Body]| bodies.iter().map(|b| b.mass).sum::<f64>();
+        let (mass_before, momentum_before) = (mass(&system.bodies), momentum(&system.bodies));
+
+        let events = merge_overlapping(&mut system);
+        assert_eq!(events, vec![CollisionEvent::Merge { survivor: 1, absorbed: 0 }]);
+        assert_eq!(system.bodies.len(), 2);
+        assert!((mass(&system.bodies) - mass_before).abs() <= 1.0e-12 * mass_before);
+        assert!((momentum(&system.bodies) - momentum_before).length() <= 1.0e-12 * momentum_before.length());
+
+        let merged = system.bodies[0];
+        assert!((merged.position - Vec2::new(0.625e6, 0.0)).length() < 1.0e-6);
+        assert!((merged.radius - 9.0e18_f64.cbrt()).abs() < 1.0e-3);
+    }
+}
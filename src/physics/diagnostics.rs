@@ -0,0 +1,150 @@
+// conserved quantities of the n-body system, used to catch integrator or softening
+// regressions without having to eyeball the orbit trails
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{Body, Vec2, G};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Conserved {
+    pub energy: f64,           // kinetic + (softened) potential, joules
+    pub momentum: Vec2,        // kg m/s
+    pub angular_momentum: f64, // about the origin, kg m^2/s (out of the plane)
+}
+
+impl Conserved {
+    pub fn of(bodies: &[Body], softening: f64) -> Self {
+        let mut kinetic = 0.0;
+        let mut potential = 0.0;
+        let mut momentum = Vec2::ZERO;
+        let mut angular_momentum = 0.0;
+
+        for (i, body) in bodies.iter().enumerate() {
+            kinetic += 0.5 * body.mass * body.velocity.length_squared();
+            momentum += body.velocity * body.mass;
+            angular_momentum += body.mass * body.position.cross(body.velocity);
+
+            // the same softened potential the forces are derived from
+            for other in &bodies[i + 1..] {
+                let distance_squared =
+                    (other.position - body.position).length_squared() + softening * softening;
+                potential -= G * body.mass * other.mass / distance_squared.sqrt();
+            }
+        }
+
+        Self {
+            energy: kinetic + potential,
+            momentum,
+            angular_momentum,
+        }
+    }
+}
+
+// change of the conserved quantities since the start of the run, as fractions
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Drift {
+    pub energy: f64,
+    pub momentum: f64, // relative to the total momentum magnitude of all bodies
+    pub angular_momentum: f64,
+}
+
+impl Drift {
+    pub fn largest(&self) -> f64 {
+        self.energy.abs().max(self.momentum.abs()).max(self.angular_momentum.abs())
+    }
+
+    pub fn within(&self, tolerance: f64) -> bool {
+        self.largest() <= tolerance
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Diagnostics {
+    pub initial: Conserved,
+    pub current: Conserved,
+    momentum_scale: f64, // sum of |m v| at the start, since the total is often ~0
+}
+
+impl Diagnostics {
+    pub fn new(bodies: &[Body], softening: f64) -> Self {
+        let initial = Conserved::of(bodies, softening);
+        Self {
+            initial,
+            current: initial,
+            momentum_scale: bodies.iter().map(|b| b.mass * b.velocity.length()).sum(),
+        }
+    }
+
+    pub fn update(&mut self, bodies: &[Body], softening: f64) {
+        self.current = Conserved::of(bodies, softening);
+    }
+
+    pub fn drift(&self) -> Drift {
+        Drift {
+            energy: relative(self.current.energy, self.initial.energy),
+            momentum: if self.momentum_scale > 0.0 {
+                (self.current.momentum - self.initial.momentum).length() / self.momentum_scale
+            } else {
+                0.0
+            },
+            angular_momentum: relative(self.current.angular_momentum, self.initial.angular_momentum),
+        }
+    }
+}
+
+fn relative(current: f64, initial: f64) -> f64 {
+    if initial == 0.0 {
+        current - initial
+    } else {
+        (current - initial) / initial.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Drift;
+    use crate::physics::testing::{earth_and_mars, DAY};
+    use crate::physics::{Integrator, System};
+
+    const YEARS: usize = 10;
+
+    // drift after `YEARS` years of one day frames
+    fn drift_after_years(mut system: System) -> Drift {
+        for _ in 0..YEARS * 365 {
+            system.advance(DAY);
+        }
+        system.drift()
+    }
+
+    fn fixed_step_drift(integrator: Integrator) -> Drift {
+        let mut system = earth_and_mars();
+        system.adaptive = false;
+        system.integrator = integrator;
+        drift_after_years(system)
+    }
+
+    // first order, but symplectic: the energy error stays bounded and the momenta exact
+    #[test]
+    fn semi_implicit_euler_drift() {
+        assert!(fixed_step_drift(Integrator::SemiImplicitEuler).within(1.0e-3));
+    }
+
+    #[test]
+    fn velocity_verlet_drift() {
+        assert!(fixed_step_drift(Integrator::VelocityVerlet).within(1.0e-6));
+    }
+
+    #[test]
+    fn rk4_drift() {
+        assert!(fixed_step_drift(Integrator::Rk4).within(1.0e-8));
+    }
+
+    #[test]
+    fn yoshida4_drift() {
+        assert!(fixed_step_drift(Integrator::Yoshida4).within(1.0e-9));
+    }
+
+    #[test]
+    fn adaptive_drift() {
+        assert!(drift_after_years(earth_and_mars()).within(1.0e-9));
+    }
+}
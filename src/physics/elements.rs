@@ -0,0 +1,197 @@
+// classical orbital elements <-> state vectors relative to a primary.
+// the simulation is planar, so the inclination is either 0 (prograde, counter-clockwise)
+// or pi (retrograde) and the ascending node is fixed on the +x axis.
+use std::f64::consts::PI;
+
+use super::kepler::{eccentric_anomaly, hyperbolic_anomaly, normalize_angle};
+use super::Vec2;
+
+// below this eccentricity the orbit is treated as circular and periapsis is put on +x
+const CIRCULAR: f64 = 1.0e-10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f64,       // m, negative for hyperbolic orbits
+    pub eccentricity: f64,          // 0 circle, <1 ellipse, >=1 escape
+    pub inclination: f64,           // rad, 0 or pi in the plane of the simulation
+    pub argument_of_periapsis: f64, // rad, from the +x axis in the direction of motion
+    pub true_anomaly: f64,          // rad, from periapsis in the direction of motion
+    pub period: f64,                // s, infinite for unbound orbits
+    pub periapsis: f64,             // m, closest distance to the primary's center
+    pub apoapsis: f64,              // m, infinite for unbound orbits
+}
+
+impl OrbitalElements {
+    // elements of the orbit through `position` with `velocity`, both relative to a
+    // primary with gravitational parameter `mu` (G * M)
+    pub fn from_state(position: Vec2, velocity: Vec2, mu: f64) -> Self {
+        let r = position.length();
+        let speed_squared = velocity.length_squared();
+        let h = position.cross(velocity);
+        let retrograde = h < 0.0;
+
+        // eccentricity vector points at periapsis
+        let e_vector = (position * (speed_squared - mu / r) - velocity * position.dot(velocity)) / mu;
+        let eccentricity = e_vector.length();
+        let energy = speed_squared / 2.0 - mu / r;
+        let semi_major_axis = -mu / (2.0 * energy);
+        let semi_latus_rectum = h * h / mu;
+
+        // angles grow in the direction of motion, so a retrograde orbit measures clockwise
+        let sense = if retrograde { -1.0 } else { 1.0 };
+        let periapsis_direction = if eccentricity > CIRCULAR {
+            e_vector.angle()
+        } else {
+            0.0
+        };
+        let argument_of_periapsis = normalize_angle(sense * periapsis_direction);
+        let true_anomaly = normalize_angle(sense * (position.angle() - periapsis_direction));
+
+        let bound = eccentricity < 1.0;
+        Self {
+            semi_major_axis,
+            eccentricity,
+            inclination: if retrograde { PI } else { 0.0 },
+            argument_of_periapsis,
+            true_anomaly,
+            period: if bound {
+                2.0 * PI * (semi_major_axis.powi(3) / mu).sqrt()
+            } else {
+                f64::INFINITY
+            },
+            periapsis: semi_latus_rectum / (1.0 + eccentricity),
+            apoapsis: if bound {
+                semi_latus_rectum / (1.0 - eccentricity)
+            } else {
+                f64::INFINITY
+            },
+        }
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.eccentricity < 1.0
+    }
+
+    pub fn is_retrograde(&self) -> bool {
+        self.inclination > PI / 2.0
+    }
+
+    // semi-latus rectum p = a (1 - e^2), also valid for hyperbolas
+    pub fn semi_latus_rectum(&self) -> f64 {
+        self.periapsis * (1.0 + self.eccentricity)
+    }
+
+    // radians per second swept by the mean anomaly (using |a| for hyperbolas)
+    pub fn mean_motion(&self, mu: f64) -> f64 {
+        (mu / self.semi_major_axis.abs().powi(3)).sqrt()
+    }
+
+    // mean anomaly at the current true anomaly, grows linearly with time
+    pub fn mean_anomaly(&self) -> f64 {
+        let e = self.eccentricity;
+        let half = (self.true_anomaly / 2.0).tan();
+        if self.is_bound() {
+            let e_anomaly = 2.0 * (((1.0 - e) / (1.0 + e)).sqrt() * half).atan();
+            e_anomaly - e * e_anomaly.sin()
+        } else {
+            let h_anomaly = 2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * half).atanh();
+            e * h_anomaly.sinh() - h_anomaly
+        }
+    }
+
+    // seconds until the next periapsis pass, negative on a hyperbola already past it
+    pub fn time_to_periapsis(&self, mu: f64) -> f64 {
+        let mean_anomaly = if self.is_bound() {
+            self.mean_anomaly().rem_euclid(2.0 * PI) - 2.0 * PI
+        } else {
+            self.mean_anomaly()
+        };
+        -mean_anomaly / self.mean_motion(mu)
+    }
+
+    // the same orbit `dt` seconds later: only the true anomaly moves
+    pub fn propagate(&self, dt: f64, mu: f64) -> Self {
+        let e = self.eccentricity;
+        let mean_anomaly = self.mean_anomaly() + self.mean_motion(mu) * dt;
+        let true_anomaly = if self.is_bound() {
+            let e_anomaly = eccentric_anomaly(mean_anomaly, e);
+            2.0 * ((1.0 + e).sqrt() * (e_anomaly / 2.0).sin())
+                .atan2((1.0 - e).sqrt() * (e_anomaly / 2.0).cos())
+        } else {
+            let h_anomaly = hyperbolic_anomaly(mean_anomaly, e);
+            2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (h_anomaly / 2.0).tanh()).atan()
+        };
+        Self {
+            true_anomaly: normalize_angle(true_anomaly),
+            ..*self
+        }
+    }
+
+    // position and velocity relative to the primary, the inverse of `from_state`
+    pub fn to_state(self, mu: f64) -> (Vec2, Vec2) {
+        self.state_at(self.true_anomaly, mu)
+    }
+
+    // position and velocity at another true anomaly on the same orbit
+    pub fn state_at(self, true_anomaly: f64, mu: f64) -> (Vec2, Vec2) {
+        let p = self.semi_latus_rectum();
+        let e = self.eccentricity;
+        let (sin_nu, cos_nu) = true_anomaly.sin_cos();
+        let r = p / (1.0 + e * cos_nu);
+
+        // perifocal frame: x towards periapsis, y along the motion at periapsis
+        let position = Vec2::new(r * cos_nu, r * sin_nu);
+        let velocity = Vec2::new(-sin_nu, e + cos_nu) * (mu / p).sqrt();
+
+        let (sin_w, cos_w) = self.argument_of_periapsis.sin_cos();
+        let rotate = |v: Vec2| {
+            let rotated = Vec2::new(cos_w * v.x - sin_w * v.y, sin_w * v.x + cos_w * v.y);
+            if self.is_retrograde() {
+                Vec2::new(rotated.x, -rotated.y)
+            } else {
+                rotated
+            }
+        };
+        (rotate(position), rotate(velocity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MU: f64 = 3.986e14; // earth
+
+    fn assert_round_trip(position: Vec2, velocity: Vec2) -> OrbitalElements {
+        let orbit = OrbitalElements::from_state(position, velocity, MU);
+        let (p, v) = orbit.to_state(MU);
+        assert!((p - position).length() < 1.0e-6 * position.length(), "{p:?} vs {position:?}");
+        assert!((v - velocity).length() < 1.0e-6 * velocity.length(), "{v:?} vs {velocity:?}");
+        orbit
+    }
+
+    #[test]
+    fn circular_state_round_trips() {
+        let r = 7.0e6;
+        let orbit = assert_round_trip(Vec2::new(0.0, r), Vec2::new(-(MU / r).sqrt(), 0.0));
+        assert!(orbit.eccentricity < 1.0e-9);
+        assert!((orbit.semi_major_axis - r).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn eccentric_state_round_trips() {
+        let orbit = assert_round_trip(Vec2::new(6.0e6, 4.0e6), Vec2::new(-3.0e3, 7.5e3));
+        assert!(orbit.is_bound() && orbit.eccentricity > 0.1);
+        // and clockwise
+        let orbit = assert_round_trip(Vec2::new(6.0e6, 4.0e6), Vec2::new(3.0e3, -7.5e3));
+        assert!(orbit.is_retrograde());
+    }
+
+    #[test]
+    fn hyperbolic_state_round_trips() {
+        let orbit = assert_round_trip(Vec2::new(-2.0e7, 1.0e7), Vec2::new(6.0e3, -1.0e3));
+        assert!(!orbit.is_bound());
+        assert!(orbit.semi_major_axis < 0.0);
+        assert!(orbit.period.is_infinite() && orbit.apoapsis.is_infinite());
+    }
+}
@@ -0,0 +1,246 @@
+// offline planetary positions from mean orbital elements at J2000 and their rates,
+// so the solar system can start at a real calendar date without network access
+use super::kepler::eccentric_anomaly;
+use super::{System, Vec2, AU, G};
+
+// julian date of the J2000 epoch (2000-01-01 12:00 TT)
+pub const J2000: f64 = 2_451_545.0;
+
+const DAYS_PER_CENTURY: f64 = 36525.0;
+
+// keplerian elements at J2000 and their rates per julian century, valid 1800-2050
+// (Standish, "Keplerian Elements for Approximate Positions of the Major Planets", table 1)
+struct MeanElements {
+    name: &'static str,
+    semi_major_axis: [f64; 2],       // au, au/cy
+    eccentricity: [f64; 2],          // -, 1/cy
+    inclination: [f64; 2],           // deg, deg/cy
+    mean_longitude: [f64; 2],        // deg, deg/cy
+    longitude_of_perihelion: [f64; 2], // deg, deg/cy
+    longitude_of_node: [f64; 2],     // deg, deg/cy
+}
+
+const ELEMENTS: [MeanElements; 8] = [
+    MeanElements {
+        name: "Mercury",
+        semi_major_axis: [0.387_099_27, 0.000_000_37],
+        eccentricity: [0.205_635_93, 0.000_019_06],
+        inclination: [7.004_979_02, -0.005_947_49],
+        mean_longitude: [252.250_323_50, 149_472.674_111_75],
+        longitude_of_perihelion: [77.457_796_28, 0.160_476_89],
+        longitude_of_node: [48.330_765_93, -0.125_340_81],
+    },
+    MeanElements {
+        name: "Venus",
+        semi_major_axis: [0.723_335_66, 0.000_003_90],
+        eccentricity: [0.006_776_72, -0.000_041_07],
+        inclination: [3.394_676_05, -0.000_788_90],
+        mean_longitude: [181.979_099_50, 58_517.815_387_29],
+        longitude_of_perihelion: [131.602_467_18, 0.002_683_29],
+        longitude_of_node: [76.679_842_55, -0.277_694_18],
+    },
+    // earth-moon barycenter
+    MeanElements {
+        name: "Earth",
+        semi_major_axis: [1.000_002_61, 0.000_005_62],
+        eccentricity: [0.016_711_23, -0.000_043_92],
+        inclination: [-0.000_015_31, -0.012_946_68],
+        mean_longitude: [100.464_571_66, 35_999.372_449_81],
+        longitude_of_perihelion: [102.937_681_93, 0.323_273_64],
+        longitude_of_node: [0.0, 0.0],
+    },
+    MeanElements {
+        name: "Mars",
+        semi_major_axis: [1.523_710_34, 0.000_018_47],
+        eccentricity: [0.093_394_10, 0.000_078_82],
+        inclination: [1.849_691_42, -0.008_131_31],
+        mean_longitude: [-4.553_432_05, 19_140.302_684_99],
+        longitude_of_perihelion: [-23.943_629_59, 0.444_410_88],
+        longitude_of_node: [49.559_538_91, -0.292_573_43],
+    },
+    MeanElements {
+        name: "Jupiter",
+        semi_major_axis: [5.202_887_00, -0.000_116_07],
+        eccentricity: [0.048_386_24, -0.000_132_53],
+        inclination: [1.304_396_95, -0.001_837_14],
+        mean_longitude: [34.396_440_51, 3_034.746_127_75],
+        longitude_of_perihelion: [14.728_479_83, 0.212_526_68],
+        longitude_of_node: [100.473_909_09, 0.204_691_06],
+    },
+    MeanElements {
+        name: "Saturn",
+        semi_major_axis: [9.536_675_94, -0.001_250_60],
+        eccentricity: [0.053_861_79, -0.000_509_91],
+        inclination: [2.485_991_87, 0.001_936_09],
+        mean_longitude: [49.954_244_23, 1_222.493_622_01],
+        longitude_of_perihelion: [92.598_878_31, -0.418_972_16],
+        longitude_of_node: [113.662_424_48, -0.288_677_94],
+    },
+    MeanElements {
+        name: "Uranus",
+        semi_major_axis: [19.189_164_64, -0.001_961_76],
+        eccentricity: [0.047_257_44, -0.000_043_97],
+        inclination: [0.772_637_83, -0.002_429_39],
+        mean_longitude: [313.238_104_51, 428.482_027_85],
+        longitude_of_perihelion: [170.954_276_30, 0.408_052_81],
+        longitude_of_node: [74.016_925_03, 0.042_405_89],
+    },
+    MeanElements {
+        name: "Neptune",
+        semi_major_axis: [30.069_922_76, 0.000_262_91],
+        eccentricity: [0.008_590_48, 0.000_051_05],
+        inclination: [1.770_043_47, 0.000_353_72],
+        mean_longitude: [-55.120_029_69, 218.459_453_25],
+        longitude_of_perihelion: [44.964_762_27, -0.322_414_64],
+        longitude_of_node: [131.784_225_74, -0.012_627_24],
+    },
+];
+
+// heliocentric ecliptic position (m) and velocity (m/s) of `name` at julian date `jd`,
+// projected onto the ecliptic plane. `mu` is G * (sun mass + planet mass).
+// returns None for bodies that are not in the table.
+pub fn heliocentric_state(name: &str, jd: f64, mu: f64) -> Option<(Vec2, Vec2)> {
+    let elements = ELEMENTS.iter().find(|e| e.name.eq_ignore_ascii_case(name))?;
+    let t = (jd - J2000) / DAYS_PER_CENTURY;
+    let at = |[value, rate]: [f64; 2]| value + rate * t;
+
+    let a = at(elements.semi_major_axis) * AU;
+    let e = at(elements.eccentricity);
+    let inclination = at(elements.inclination).to_radians();
+    let node = at(elements.longitude_of_node).to_radians();
+    let perihelion = at(elements.longitude_of_perihelion).to_radians();
+    let argument_of_perihelion = perihelion - node;
+    let mean_anomaly = at(elements.mean_longitude).to_radians() - perihelion;
+
+    // position and velocity in the orbital plane, x towards perihelion
+    let big_e = eccentric_anomaly(mean_anomaly, e);
+    let root = (1.0 - e * e).sqrt();
+    let n = (mu / a.powi(3)).sqrt();
+    let e_dot = n / (1.0 - e * big_e.cos());
+    let orbital_position = Vec2::new(a * (big_e.cos() - e), a * root * big_e.sin());
+    let orbital_velocity = Vec2::new(-a * big_e.sin() * e_dot, a * root * big_e.cos() * e_dot);
+
+    // rotate into the ecliptic and drop the out-of-plane component
+    let (sin_w, cos_w) = argument_of_perihelion.sin_cos();
+    let (sin_o, cos_o) = node.sin_cos();
+    let cos_i = inclination.cos();
+    let to_ecliptic = |v: Vec2| {
+        Vec2::new(
+            (cos_w * cos_o - sin_w * sin_o * cos_i) * v.x
+                + (-sin_w * cos_o - cos_w * sin_o * cos_i) * v.y,
+            (cos_w * sin_o + sin_w * cos_o * cos_i) * v.x
+                + (-sin_w * sin_o + cos_w * cos_o * cos_i) * v.y,
+        )
+    };
+
+    Some((to_ecliptic(orbital_position), to_ecliptic(orbital_velocity)))
+}
+
+// move every body whose name is in the table to where it was on julian date `jd`,
+// relative to the sun, then give the sun the velocity that cancels the total momentum.
+// `names[i]` names `system.bodies[i]`; bodies not in the table keep their state.
+pub fn place_at_epoch(system: &mut System, names: &[&str], sun: usize, jd: f64) {
+    let sun_body = system.bodies[sun];
+    for (i, name) in names.iter().enumerate() {
+        if i == sun {
+            continue;
+        }
+        let body = &mut system.bodies[i];
+        let mu = gravitational_parameter(sun_body.mass, body.mass);
+        if let Some((position, velocity)) = heliocentric_state(name, jd, mu) {
+            body.position = sun_body.position + position;
+            body.velocity = sun_body.velocity + velocity;
+        }
+    }
+
+    let momentum = system
+        .bodies
+        .iter()
+        .fold(Vec2::ZERO, |total, b| total + b.velocity * b.mass);
+    let sun_body = &mut system.bodies[sun];
+    sun_body.velocity -= momentum / sun_body.mass;
+
+    system.time = 0.0;
+    system.reset_diagnostics();
+}
+
+// G * (m1 + m2), the gravitational parameter of a two-body orbit
+pub fn gravitational_parameter(primary_mass: f64, secondary_mass: f64) -> f64 {
+    G * (primary_mass + secondary_mass)
+}
+
+// julian date at 0h of a gregorian calendar date (Meeus, astronomical algorithms ch. 7)
+pub fn julian_date(year: i32, month: u32, day: u32) -> f64 {
+    let (mut y, mut m) = (f64::from(year), f64::from(month));
+    if month <= 2 {
+        y -= 1.0;
+        m += 12.0;
+    }
+    let a = (y / 100.0).floor();
+    let b = 2.0 - a + (a / 4.0).floor();
+    (365.25 * (y + 4716.0)).floor() + (30.6001 * (m + 1.0)).floor() + f64::from(day) + b - 1524.5
+}
+
+// gregorian (year, month, day) containing julian date `jd`
+pub fn calendar_date(jd: f64) -> (i32, u32, u32) {
+    let z = (jd + 0.5).floor();
+    let f = jd + 0.5 - z;
+    let alpha = ((z - 1_867_216.25) / 36524.25).floor();
+    let a = z + 1.0 + alpha - (alpha / 4.0).floor();
+    let b = a + 1524.0;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+
+    let day = (b - d - (30.6001 * e).floor() + f).floor() as u32;
+    let month = if e < 14.0 { e - 1.0 } else { e - 13.0 } as u32;
+    let year = if month > 2 { c - 4716.0 } else { c - 4715.0 } as i32;
+    (year, month, day)
+}
+
+// "YYYY-MM-DD" for julian date `jd`
+pub fn format_date(jd: f64) -> String {
+    let (year, month, day) = calendar_date(jd);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUN: f64 = 1.98892e30;
+
+    // heliocentric ecliptic longitude (deg, 0..360) and distance (au) of `name` at `jd`
+    fn where_is(name: &str, jd: f64) -> (f64, f64) {
+        let (position, _) = heliocentric_state(name, jd, gravitational_parameter(SUN, 0.0)).unwrap();
+        (position.angle().to_degrees().rem_euclid(360.0), position.length() / AU)
+    }
+
+    #[test]
+    fn julian_dates_round_trip() {
+        assert_eq!(julian_date(2000, 1, 1) + 0.5, J2000);
+        assert_eq!(calendar_date(julian_date(2003, 8, 27)), (2003, 8, 27));
+        assert_eq!(format_date(julian_date(2026, 2, 28) + 1.0), "2026-03-01");
+    }
+
+    #[test]
+    fn earth_at_j2000() {
+        // the sun's true longitude at j2000 is 280.38 deg and earth is opposite it,
+        // two days before perihelion
+        let (longitude, distance) = where_is("Earth", J2000);
+        assert!((longitude - 100.38).abs() < 0.05, "{longitude}");
+        assert!((distance - 0.9833).abs() < 0.001, "{distance}");
+    }
+
+    #[test]
+    fn mars_in_opposition_on_2003_08_28() {
+        // the closest opposition in 60,000 years: mars near perihelion, straight
+        // outward from earth at about 335 deg
+        let jd = julian_date(2003, 8, 28);
+        let (earth, _) = where_is("Earth", jd);
+        let (mars, distance) = where_is("Mars", jd);
+        assert!((earth - 335.0).abs() < 1.0, "{earth}");
+        assert!((mars - earth).abs() < 1.0, "{mars} vs {earth}");
+        assert!((distance - 1.38).abs() < 0.01, "{distance}");
+    }
+}
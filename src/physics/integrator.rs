@@ -0,0 +1,133 @@
+// fixed-step integrators; every scheme advances all bodies simultaneously
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{Body, Vec2};
+
+// yoshida's 4th order coefficients, built from the triple-jump of a leapfrog step
+const CBRT_2: f64 = 1.259_921_049_894_873_2;
+const YOSHIDA_W1: f64 = 1.0 / (2.0 - CBRT_2);
+const YOSHIDA_W0: f64 = -CBRT_2 / (2.0 - CBRT_2);
+const YOSHIDA_C: [f64; 4] = [
+    YOSHIDA_W1 / 2.0,
+    (YOSHIDA_W0 + YOSHIDA_W1) / 2.0,
+    (YOSHIDA_W0 + YOSHIDA_W1) / 2.0,
+    YOSHIDA_W1 / 2.0,
+];
+const YOSHIDA_D: [f64; 3] = [YOSHIDA_W1, YOSHIDA_W0, YOSHIDA_W1];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub enum Integrator {
+    // first order, what the game originally shipped with
+    SemiImplicitEuler,
+    // second order symplectic (kick-drift-kick leapfrog)
+    VelocityVerlet,
+    // classic 4th order runge-kutta, accurate but not symplectic
+    Rk4,
+    // 4th order symplectic, keeps orbits closed over long runs
+    #[default]
+    Yoshida4,
+}
+
+impl Integrator {
+    pub const ALL: [Self; 4] = [
+        Self::SemiImplicitEuler,
+        Self::VelocityVerlet,
+        Self::Rk4,
+        Self::Yoshida4,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SemiImplicitEuler => "semi-implicit euler",
+            Self::VelocityVerlet => "velocity verlet",
+            Self::Rk4 => "rk4",
+            Self::Yoshida4 => "yoshida 4",
+        }
+    }
+
+    // the integrator after this one, wrapping around
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&s| s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    // advance `bodies` by dt seconds; `accelerations` returns the acceleration of
+    // every body for a given configuration
+    pub fn step<F>(self, bodies: &mut [Body], dt: f64, accelerations: F)
+    where
+        F: Fn(&[Body]) -> Vec<Vec2>,
+    {
+        match self {
+            Self::SemiImplicitEuler => {
+                kick(bodies, &accelerations(bodies), dt);
+                drift(bodies, dt);
+            }
+            Self::VelocityVerlet => {
+                kick(bodies, &accelerations(bodies), dt / 2.0);
+                drift(bodies, dt);
+                kick(bodies, &accelerations(bodies), dt / 2.0);
+            }
+            Self::Rk4 => rk4(bodies, dt, &accelerations),
+            Self::Yoshida4 => {
+                for i in 0..YOSHIDA_D.len() {
+                    drift(bodies, YOSHIDA_C[i] * dt);
+                    kick(bodies, &accelerations(bodies), YOSHIDA_D[i] * dt);
+                }
+                drift(bodies, YOSHIDA_C[3] * dt);
+            }
+        }
+    }
+}
+
+fn kick(bodies: &mut [Body], accelerations: &[Vec2], dt: f64) {
+    for (body, &acceleration) in bodies.iter_mut().zip(accelerations) {
+        body.kick(acceleration, dt);
+    }
+}
+
+fn drift(bodies: &mut [Body], dt: f64) {
+    for body in bodies.iter_mut() {
+        body.drift(dt);
+    }
+}
+
+// configuration offset from `start` by the derivative (velocity, acceleration) times h
+fn offset(start: &[Body], velocities: &[Vec2], accelerations: &[Vec2], h: f64) -> Vec<Body> {
+    start
+        .iter()
+        .zip(velocities.iter().zip(accelerations))
+        .map(|(body, (&v, &a))| Body {
+            position: body.position + v * h,
+            velocity: body.velocity + a * h,
+            ..*body
+        })
+        .collect()
+}
+
+fn velocities(bodies: &[Body]) -> Vec<Vec2> {
+    bodies.iter().map(|b| b.velocity).collect()
+}
+
+fn rk4<F>(bodies: &mut [Body], dt: f64, accelerations: &F)
+where
+    F: Fn(&[Body]) -> Vec<Vec2>,
+{
+    let start = bodies.to_vec();
+
+    let v1 = velocities(&start);
+    let a1 = accelerations(&start);
+    let s2 = offset(&start, &v1, &a1, dt / 2.0);
+    let v2 = velocities(&s2);
+    let a2 = accelerations(&s2);
+    let s3 = offset(&start, &v2, &a2, dt / 2.0);
+    let v3 = velocities(&s3);
+    let a3 = accelerations(&s3);
+    let s4 = offset(&start, &v3, &a3, dt);
+    let v4 = velocities(&s4);
+    let a4 = accelerations(&s4);
+
+    for (i, body) in bodies.iter_mut().enumerate() {
+        body.position += (v1[i] + (v2[i] + v3[i]) * 2.0 + v4[i]) * (dt / 6.0);
+        body.velocity += (a1[i] + (a2[i] + a3[i]) * 2.0 + a4[i]) * (dt / 6.0);
+    }
+}
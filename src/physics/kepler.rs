@@ -0,0 +1,42 @@
+// solutions of kepler's equation, shared by the ephemeris and the orbit code
+use std::f64::consts::PI;
+
+const TOLERANCE: f64 = 1.0e-12;
+const MAX_ITERATIONS: usize = 50;
+
+// wrap an angle into [-pi, pi)
+pub fn normalize_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+// eccentric anomaly E for mean anomaly M on an ellipse: M = E - e sin E
+pub fn eccentric_anomaly(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    let m = normalize_angle(mean_anomaly);
+    let mut e_anomaly = if eccentricity < 0.8 { m } else { PI.copysign(m) };
+
+    for _ in 0..MAX_ITERATIONS {
+        let f = e_anomaly - eccentricity * e_anomaly.sin() - m;
+        let delta = f / (1.0 - eccentricity * e_anomaly.cos());
+        e_anomaly -= delta;
+        if delta.abs() < TOLERANCE {
+            break;
+        }
+    }
+    e_anomaly
+}
+
+// hyperbolic anomaly H for mean anomaly M on a hyperbola: M = e sinh H - H
+pub fn hyperbolic_anomaly(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    let m = mean_anomaly;
+    let mut h_anomaly = (m / eccentricity).asinh();
+
+    for _ in 0..MAX_ITERATIONS {
+        let f = eccentricity * h_anomaly.sinh() - h_anomaly - m;
+        let delta = f / (eccentricity * h_anomaly.cosh() - 1.0);
+        h_anomaly -= delta;
+        if delta.abs() < TOLERANCE * (1.0 + h_anomaly.abs()) {
+            break;
+        }
+    }
+    h_anomaly
+}
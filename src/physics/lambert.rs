@@ -0,0 +1,108 @@
+// lambert's problem: the orbit that connects two positions in a given time. solved
+// with universal variables and stumpff functions (curtis, orbital mechanics for
+// engineering students, algorithm 5.2), zero revolutions only.
+use std::f64::consts::PI;
+
+use super::Vec2;
+
+const TOLERANCE: f64 = 1.0e-9;
+const MAX_ITERATIONS: usize = 200;
+
+fn stumpff_c(z: f64) -> f64 {
+    if z > 0.0 {
+        (1.0 - z.sqrt().cos()) / z
+    } else if z < 0.0 {
+        ((-z).sqrt().cosh() - 1.0) / -z
+    } else {
+        0.5
+    }
+}
+
+fn stumpff_s(z: f64) -> f64 {
+    if z > 0.0 {
+        let s = z.sqrt();
+        (s - s.sin()) / s.powi(3)
+    } else if z < 0.0 {
+        let s = (-z).sqrt();
+        (s.sinh() - s) / s.powi(3)
+    } else {
+        1.0 / 6.0
+    }
+}
+
+// velocities at `r1` and at `r2` of the orbit around a body with gravitational
+// parameter `mu` that goes from one to the other in `time_of_flight` seconds, moving
+// counter-clockwise when `prograde`. `None` when there is no such orbit (a transfer of
+// exactly 0 or 180 degrees, or no convergence).
+pub fn lambert(mu: f64, r1: Vec2, r2: Vec2, time_of_flight: f64, prograde: bool) -> Option<(Vec2, Vec2)> {
+    let (d1, d2) = (r1.length(), r2.length());
+    if time_of_flight <= 0.0 || d1 == 0.0 || d2 == 0.0 {
+        return None;
+    }
+
+    // angle swept in the direction of motion
+    let cos_angle = (r1.dot(r2) / (d1 * d2)).clamp(-1.0, 1.0);
+    let short_way = (r1.cross(r2) >= 0.0) == prograde;
+    let angle = if short_way {
+        cos_angle.acos()
+    } else {
+        2.0 * PI - cos_angle.acos()
+    };
+    let a = angle.sin() * (d1 * d2 / (1.0 - cos_angle)).sqrt();
+    if !a.is_finite() || a.abs() < TOLERANCE {
+        return None;
+    }
+
+    let y = |z: f64| d1 + d2 + a * (z * stumpff_s(z) - 1.0) / stumpff_c(z).sqrt();
+    let time = |z: f64| {
+        let y = y(z);
+        ((y / stumpff_c(z)).powf(1.5) * stumpff_s(z) + a * y.sqrt()) / mu.sqrt()
+    };
+
+    // the flight time grows with z; bisect between a hyperbola and the one-revolution limit
+    let (mut low, mut high) = (-4.0 * PI * PI, 4.0 * PI * PI - TOLERANCE);
+    let mut z = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+        z = (low + high) / 2.0;
+        // y < 0 means z is too small for this geometry
+        if y(z) < 0.0 || time(z) < time_of_flight {
+            low = z;
+        } else {
+            high = z;
+        }
+        if high - low < TOLERANCE {
+            break;
+        }
+    }
+    let y = y(z);
+    if y.is_nan() || y <= 0.0 || ((time(z) - time_of_flight) / time_of_flight).abs() > 1.0e-6 {
+        return None;
+    }
+
+    // lagrange coefficients
+    let f = 1.0 - y / d1;
+    let g = a * (y / mu).sqrt();
+    let g_dot = 1.0 - y / d2;
+    Some(((r2 - r1 * f) / g, (r2 * g_dot - r1) / g))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{hohmann, AU, G};
+
+    // half way round in the hohmann time is the hohmann transfer. exactly 180 degrees
+    // has no unique plane, so stop a little short of it.
+    #[test]
+    fn matches_hohmann_from_earth_to_mars() {
+        let mu = G * 1.98892e30;
+        let (r1, r2) = (AU, 1.523_679 * AU);
+        let transfer = hohmann(mu, r1, r2);
+        let arrival = Vec2::from_angle(PI - 1.0e-3) * r2;
+        let (leave, arrive) = lambert(mu, Vec2::new(r1, 0.0), arrival, transfer.time_of_flight, true).unwrap();
+        let departure = leave.length() - (mu / r1).sqrt();
+        let capture = (mu / r2).sqrt() - arrive.length();
+        assert!((departure - transfer.departure_delta_v).abs() < 1.0);
+        assert!((capture - transfer.arrival_delta_v).abs() < 1.0);
+    }
+}
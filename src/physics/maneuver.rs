@@ -0,0 +1,44 @@
+// planned impulsive burns. the simulation is planar, so a burn has a prograde and a
+// radial component only: there is no orbit normal to burn along.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::Vec2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Maneuver {
+    pub time: f64,     // simulation time of the burn, the middle of it for a real engine
+    pub prograde: f64, // m/s along the velocity relative to the primary, negative retrograde
+    pub radial: f64,   // m/s across the velocity, away from the primary, negative inwards
+}
+
+impl Maneuver {
+    pub fn new(time: f64) -> Self {
+        Self {
+            time,
+            ..Self::default()
+        }
+    }
+
+    // total m/s
+    pub fn delta_v(&self) -> f64 {
+        self.prograde.hypot(self.radial)
+    }
+
+    // unit prograde and radial-out directions for a craft at `position` moving at
+    // `velocity`, both relative to the primary
+    pub fn frame(position: Vec2, velocity: Vec2) -> (Vec2, Vec2) {
+        let prograde = velocity.normalized();
+        let radial = prograde.perp();
+        if radial.dot(position) < 0.0 {
+            (prograde, -radial)
+        } else {
+            (prograde, radial)
+        }
+    }
+
+    // the burn as a world-space velocity change, for a craft in the given state
+    pub fn velocity_change(&self, position: Vec2, velocity: Vec2) -> Vec2 {
+        let (prograde, radial) = Self::frame(position, velocity);
+        prograde * self.prograde + radial * self.radial
+    }
+}
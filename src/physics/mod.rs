@@ -0,0 +1,473 @@
+// headless n-body simulation: bodies, forces and integration.
+// nothing in here may depend on turbo so it can be unit-tested and reused in tools.
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+mod adaptive;
+mod atmosphere;
+mod capture;
+mod collision;
+mod diagnostics;
+mod elements;
+pub mod ephemeris;
+mod integrator;
+pub mod kepler;
+mod lambert;
+mod maneuver;
+mod predict;
+mod propulsion;
+mod soi;
+#[cfg(test)]
+mod testing;
+mod transfer;
+
+pub use adaptive::AdaptiveStepper;
+pub use atmosphere::{drag_acceleration, dynamic_pressure, heat_flux, Atmosphere};
+pub use capture::{aerocapture_periapsis, approach, capture_burn, is_stable, periapsis_raise, periapsis_trim, Approach};
+pub use collision::{craft_contact, merge_overlapping, CollisionEvent};
+pub use diagnostics::{Diagnostics, Drift};
+pub use elements::OrbitalElements;
+pub use integrator::Integrator;
+pub use lambert::lambert;
+pub use maneuver::Maneuver;
+pub use predict::{predict, Marker, NodeMarker, Prediction, PredictionRequest};
+pub use propulsion::{Burn, Propulsion, Stage, STANDARD_GRAVITY};
+pub use soi::coast;
+pub use transfer::{
+    departure_burn, escape_periapsis_angle, hohmann, impact_offset, launch_window, Hohmann, LaunchWindow,
+};
+
+// astronomical unit in meters (average distance from Earth to the Sun)
+pub const AU: f64 = 149.6e6 * 1000.0;
+
+// gravitational constant in the SI unit of m^3 kg^-1 s^-2
+pub const G: f64 = 6.67428e-11;
+
+// used to avoid numerical instability in gravitational calculations at close distances.
+// the adaptive stepper resolves close encounters, so this only has to guard against a
+// zero distance; 1 km is far inside any planet and changes surface gravity by ~1e-8
+pub const SOFTENING_FACTOR: f64 = 1.0e3;
+
+// 2d vector in world space (meters, meters per second, ...)
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub const ZERO: Self = Self { x: 0.0, y: 0.0 };
+
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn from_angle(angle: f64) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+
+    pub fn dot(self, other: Self) -> f64 {
+        self.x.mul_add(other.x, self.y * other.y)
+    }
+
+    // z component of the 3d cross product of two in-plane vectors
+    pub fn cross(self, other: Self) -> f64 {
+        self.x.mul_add(other.y, -self.y * other.x)
+    }
+
+    pub fn length_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    // unit vector in the same direction, or zero for a zero-length vector
+    pub fn normalized(self) -> Self {
+        let length = self.length();
+        if length > 0.0 {
+            self / length
+        } else {
+            Self::ZERO
+        }
+    }
+
+    // the vector rotated a quarter turn counter-clockwise
+    pub fn perp(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Self;
+    fn mul(self, factor: f64) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Div<f64> for Vec2 {
+    type Output = Self;
+    fn div(self, divisor: f64) -> Self {
+        Self::new(self.x / divisor, self.y / divisor)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+// a point mass taking part in the simulation
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Body {
+    pub position: Vec2, // meters from the origin (the sun starts there)
+    pub velocity: Vec2, // meters per second
+    pub mass: f64,      // kilograms
+    pub radius: f64,    // physical radius in meters, zero for point-like craft
+    pub spin: f64,      // rotation about its axis in rad/s, counter-clockwise
+    pub atmosphere: Option<Atmosphere>,
+}
+
+impl Body {
+    pub fn new(position: Vec2, velocity: Vec2, mass: f64) -> Self {
+        Self {
+            position,
+            velocity,
+            mass,
+            radius: 0.0,
+            spin: 0.0,
+            atmosphere: None,
+        }
+    }
+
+    // change the velocity by a constant acceleration applied for dt seconds
+    pub fn kick(&mut self, acceleration: Vec2, dt: f64) {
+        self.velocity += acceleration * dt;
+    }
+
+    // move the body along its current velocity for dt seconds
+    pub fn drift(&mut self, dt: f64) {
+        self.position += self.velocity * dt;
+    }
+
+    // velocity of the ground at `offset` from the center, carried around by the spin
+    pub fn surface_velocity(&self, offset: Vec2) -> Vec2 {
+        self.velocity + offset.perp() * self.spin
+    }
+
+    pub fn distance_to(&self, other: &Self) -> f64 {
+        (other.position - self.position).length()
+    }
+}
+
+// gravitational acceleration a body of `mass` at `source` exerts on a point at `at`
+pub fn gravitational_acceleration(source: Vec2, mass: f64, at: Vec2, softening: f64) -> Vec2 {
+    let offset = source - at;
+    let distance_squared = offset.length_squared() + softening * softening;
+    let distance = distance_squared.sqrt();
+    offset * (G * mass / (distance_squared * distance))
+}
+
+// acceleration of every body due to all the others, evaluated on the same snapshot
+pub fn accelerations(bodies: &[Body], softening: f64) -> Vec<Vec2> {
+    bodies
+        .iter()
+        .enumerate()
+        .map(|(i, body)| {
+            let mut total = Vec2::ZERO;
+            for (j, other) in bodies.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let acceleration =
+                    gravitational_acceleration(other.position, other.mass, body.position, softening);
+                if acceleration.is_finite() {
+                    total += acceleration;
+                }
+            }
+            total
+        })
+        .collect()
+}
+
+// every massive body in the simulation, advanced together
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct System {
+    pub bodies: Vec<Body>,
+    pub softening: f64, // softening length in meters
+    pub time: f64,      // simulated seconds since the start of the run
+    pub integrator: Integrator,
+    pub adaptive: bool, // use the error-controlled stepper instead of fixed steps
+    pub stepper: AdaptiveStepper,
+    pub diagnostics: Diagnostics, // conserved quantities, refreshed after every step
+}
+
+impl System {
+    pub fn new(bodies: Vec<Body>) -> Self {
+        let diagnostics = Diagnostics::new(&bodies, SOFTENING_FACTOR);
+        Self {
+            bodies,
+            softening: SOFTENING_FACTOR,
+            time: 0.0,
+            integrator: Integrator::default(),
+            adaptive: true,
+            stepper: AdaptiveStepper::default(),
+            diagnostics,
+        }
+    }
+
+    // the fixed-step integrators in turn, then back to the adaptive stepper
+    pub fn next_integrator(&mut self) {
+        let next = self.integrator.next();
+        if self.adaptive {
+            self.adaptive = false;
+            self.integrator = next;
+        } else if next == Integrator::ALL[0] {
+            self.adaptive = true;
+        } else {
+            self.integrator = next;
+        }
+    }
+
+    // take the current state as the reference the drift is measured against
+    pub fn reset_diagnostics(&mut self) {
+        self.diagnostics = Diagnostics::new(&self.bodies, self.softening);
+    }
+
+    // change in energy and momenta since the start of the run
+    pub fn drift(&self) -> Drift {
+        self.diagnostics.drift()
+    }
+
+    // acceleration at `at` due to every body, optionally ignoring one of them
+    pub fn acceleration_at(&self, at: Vec2, skip: Option<usize>) -> Vec2 {
+        let mut total = Vec2::ZERO;
+        for (i, body) in self.bodies.iter().enumerate() {
+            if Some(i) == skip {
+                continue;
+            }
+            let acceleration =
+                gravitational_acceleration(body.position, body.mass, at, self.softening);
+            if acceleration.is_finite() {
+                total += acceleration;
+            }
+        }
+        total
+    }
+
+    pub fn accelerations(&self) -> Vec<Vec2> {
+        accelerations(&self.bodies, self.softening)
+    }
+
+    // advance all bodies simultaneously by dt seconds with the selected integrator
+    pub fn step(&mut self, dt: f64) {
+        let previous = self.bodies.clone();
+        let softening = self.softening;
+        self.integrator
+            .step(&mut self.bodies, dt, |bodies| accelerations(bodies, softening));
+
+        // keep the last good state instead of propagating NaNs
+        for (body, previous) in self.bodies.iter_mut().zip(previous) {
+            if !body.position.is_finite() || !body.velocity.is_finite() {
+                *body = previous;
+            }
+        }
+        self.time += dt;
+        self.diagnostics.update(&self.bodies, self.softening);
+    }
+
+    // advance the simulation by `duration` seconds, either in one fixed step or in as
+    // many error-controlled substeps as the current configuration needs
+    pub fn advance(&mut self, duration: f64) {
+        self.advance_with_craft(duration, &mut [], &[]);
+    }
+
+    // like `advance`, carrying `craft` along as massless test particles: they feel every
+    // body's gravity plus their own `thrust` (m/s^2, held for the whole duration) but
+    // pull on nothing, and are integrated by the same stepper as the planets
+    pub fn advance_with_craft(&mut self, duration: f64, craft: &mut [Body], thrust: &[Vec2]) {
+        if !self.adaptive && craft.is_empty() {
+            self.step(duration);
+            return;
+        }
+
+        let massive = self.bodies.len();
+        let previous = self.bodies.clone();
+        let mut all = previous.clone();
+        all.extend(craft.iter().map(|c| Body { mass: 0.0, ..*c }));
+
+        let softening = self.softening;
+        let forces = |bodies: &[Body]| {
+            let mut total = accelerations(bodies, softening);
+            for (a, thrust) in total[massive..].iter_mut().zip(thrust) {
+                *a += *thrust;
+            }
+            total
+        };
+        if self.adaptive {
+            self.stepper.advance(&mut all, duration, forces);
+        } else {
+            self.integrator.step(&mut all, duration, forces);
+        }
+
+        // keep the last good state instead of propagating NaNs
+        if all.iter().any(|b| !b.position.is_finite() || !b.velocity.is_finite()) {
+            self.bodies = previous;
+            self.stepper = AdaptiveStepper::default();
+        } else {
+            self.bodies.copy_from_slice(&all[..massive]);
+            for (craft, moved) in craft.iter_mut().zip(&all[massive..]) {
+                craft.position = moved.position;
+                craft.velocity = moved.velocity;
+            }
+        }
+        self.time += duration;
+        self.diagnostics.update(&self.bodies, self.softening);
+    }
+
+    // the body pulling hardest on a point at `at`
+    pub fn strongest_pull(&self, at: Vec2) -> Option<usize> {
+        self.bodies
+            .iter()
+            .map(|b| G * b.mass / (b.position - at).length_squared())
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    // orbit of `body` around `primary`, from their relative state
+    pub fn elements(&self, body: &Body, primary: usize) -> OrbitalElements {
+        let primary = &self.bodies[primary];
+        OrbitalElements::from_state(
+            body.position - primary.position,
+            body.velocity - primary.velocity,
+            G * (primary.mass + body.mass),
+        )
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> f64 {
+        self.bodies[a].distance_to(&self.bodies[b])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::{earth_and_mars, DAY};
+
+    #[test]
+    fn gravitational_acceleration_points_at_the_source() {
+        let at = Vec2::new(3.0e6, 4.0e6);
+        let acceleration = gravitational_acceleration(Vec2::ZERO, 5.9742e24, at, 0.0);
+        assert!((acceleration.length() - G * 5.9742e24 / 25.0e12).abs() < 1.0e-12);
+        assert!((acceleration.normalized() + at.normalized()).length() < 1.0e-12);
+        // softening only ever weakens the pull
+        let softened = gravitational_acceleration(Vec2::ZERO, 5.9742e24, at, SOFTENING_FACTOR);
+        assert!(softened.length() < acceleration.length());
+    }
+
+    // a light moon on a circle around an earth-mass planet is back where it started
+    // after one period, 2 pi sqrt(r^3 / mu)
+    #[test]
+    fn circular_orbit_period() {
+        let (mass, radius) = (5.9742e24, 3.844e8);
+        let mu = G * mass;
+        let speed = (mu / radius).sqrt();
+        let start = Vec2::new(radius, 0.0);
+        let mut system = System::new(vec![
+            Body::new(Vec2::ZERO, Vec2::ZERO, mass),
+            Body::new(start, Vec2::new(0.0, speed), 1.0),
+        ]);
+        system.adaptive = false;
+        let period = 2.0 * std::f64::consts::PI * (radius.powi(3) / mu).sqrt();
+        let steps = 1000;
+        for _ in 0..steps {
+            system.advance(period / steps as f64);
+        }
+        assert!((system.bodies[1].position - start).length() < 1.0e-6 * radius);
+        assert!((system.bodies[1].velocity - Vec2::new(0.0, speed)).length() < 1.0e-6 * speed);
+    }
+
+    // after `years` of one day frames earth and mars are still on the orbits they started
+    // on. the planets pull on each other, so the perihelia may turn a little (mars' by
+    // about 5e-4 rad in 50 years); semi-implicit euler misses by 100 times more.
+    fn assert_closed_after(mut system: System, years: usize) {
+        let before: Vec<OrbitalElements> = (1..3).map(|i| system.elements(&system.bodies[i], 0)).collect();
+        for _ in 0..years * 365 {
+            system.advance(DAY);
+        }
+        for (i, before) in (1..3).zip(before) {
+            let after = system.elements(&system.bodies[i], 0);
+            let turned = kepler::normalize_angle(after.argument_of_periapsis - before.argument_of_periapsis);
+            assert!((after.semi_major_axis / before.semi_major_axis - 1.0).abs() < 1.0e-4);
+            assert!((after.eccentricity - before.eccentricity).abs() < 1.0e-4);
+            assert!(turned.abs() < 1.0e-2);
+        }
+    }
+
+    #[test]
+    fn yoshida4_keeps_orbits_closed_for_decades() {
+        let mut system = earth_and_mars();
+        system.adaptive = false;
+        system.integrator = Integrator::Yoshida4;
+        assert_closed_after(system, 50);
+    }
+
+    #[test]
+    fn adaptive_keeps_orbits_closed_for_decades() {
+        assert_closed_after(earth_and_mars(), 50);
+    }
+
+    #[test]
+    fn integrators_cycle_back_to_adaptive() {
+        let mut system = earth_and_mars();
+        let mut seen = Vec::new();
+        for _ in 0..Integrator::ALL.len() {
+            system.next_integrator();
+            assert!(!system.adaptive);
+            seen.push(system.integrator);
+        }
+        assert_eq!(seen, Integrator::ALL);
+        system.next_integrator();
+        assert!(system.adaptive);
+    }
+}
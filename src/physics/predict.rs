@@ -0,0 +1,197 @@
+// forward propagation on a copy of the simulation, for drawing where things are going
+use super::{accelerations, AdaptiveStepper, Body, Maneuver, System, Vec2};
+
+// a point of interest on the predicted path
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Marker {
+    pub time: f64,       // seconds from now
+    pub position: Vec2,  // where the craft will be
+    pub primary_position: Vec2, // where the primary will be at the same time
+    pub distance: f64,   // from the reference body's center
+}
+
+// where a planned burn happens on the predicted path, with the directions it burns in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NodeMarker {
+    pub marker: Marker,
+    pub prograde: Vec2, // unit vectors of the burn's frame
+    pub radial: Vec2,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prediction {
+    pub interval: f64,               // seconds between two points
+    pub points: Vec<Vec2>,           // craft positions, evenly spaced in time
+    pub primary_path: Vec<Vec2>,     // the primary's positions at the same times
+    pub body_paths: Vec<Vec<Vec2>>,  // same for every body, when asked for
+    pub periapsis: Option<Marker>,   // first closest point to the primary
+    pub apoapsis: Option<Marker>,    // first farthest point from the primary
+    pub closest_approach: Option<Marker>, // closest point to the target over the horizon
+    pub target_position: Option<Vec2>,    // where the target is at the closest approach
+    pub nodes: Vec<NodeMarker>,           // maneuvers within the horizon, in time order
+    pub first_burned: Option<usize>,      // first point after the first maneuver
+}
+
+pub struct PredictionRequest {
+    pub horizon: f64,           // seconds to look ahead
+    pub samples: usize,         // points along the path
+    pub primary: usize,         // body the apsides are measured from
+    pub target: Option<usize>,  // body to find the closest approach to
+    pub include_bodies: bool,   // also record every body's path
+    pub maneuvers: Vec<Maneuver>, // burns to apply on the way, as impulses
+}
+
+// propagate `craft` as a massless test particle together with every body of `system`
+// (which is left untouched) and collect its path and markers. planned maneuvers are
+// applied at their times as instant velocity changes. the path ends where it first
+// hits a body's surface.
+pub fn predict(system: &System, craft: &Body, request: &PredictionRequest) -> Prediction {
+    let mut bodies = system.bodies.clone();
+    let craft_index = bodies.len();
+    bodies.push(Body { mass: 0.0, ..*craft });
+
+    let softening = system.softening;
+    let mut stepper = AdaptiveStepper {
+        next_dt: system.stepper.next_dt,
+        ..AdaptiveStepper::default()
+    };
+    let samples = request.samples.max(2);
+    let interval = request.horizon / samples as f64;
+
+    // seconds from now, soonest first; burns already under way happen right away
+    let mut maneuvers: Vec<(f64, Maneuver)> = request
+        .maneuvers
+        .iter()
+        .map(|m| ((m.time - system.time).max(0.0), *m))
+        .collect();
+    maneuvers.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut maneuvers = maneuvers.into_iter().peekable();
+
+    let mut prediction = Prediction {
+        interval,
+        body_paths: if request.include_bodies {
+            vec![Vec::with_capacity(samples); craft_index]
+        } else {
+            Vec::new()
+        },
+        ..Prediction::default()
+    };
+
+    // previous distance to the primary and whether it was shrinking, to spot turning points
+    let mut previous_distance = None;
+    let mut falling = None;
+
+    for i in 0..=samples {
+        let time = interval * i as f64;
+        let mut reached = if i > 0 { time - interval } else { 0.0 };
+        while let Some(&(at, maneuver)) = maneuvers.peek().filter(|(at, _)| *at <= time) {
+            maneuvers.next();
+            if at > reached {
+                stepper.advance(&mut bodies, at - reached, |b| accelerations(b, softening));
+                reached = at;
+            }
+            let primary = bodies[request.primary];
+            let craft = &mut bodies[craft_index];
+            let position = craft.position - primary.position;
+            let velocity = craft.velocity - primary.velocity;
+            craft.velocity += maneuver.velocity_change(position, velocity);
+            let (prograde, radial) = Maneuver::frame(position, velocity);
+            prediction.nodes.push(NodeMarker {
+                marker: Marker {
+                    time: at,
+                    position: craft.position,
+                    primary_position: primary.position,
+                    distance: position.length(),
+                },
+                prograde,
+                radial,
+            });
+            prediction.first_burned.get_or_insert(i);
+        }
+        if time > reached {
+            stepper.advance(&mut bodies, time - reached, |b| accelerations(b, softening));
+        }
+        let position = bodies[craft_index].position;
+        if !position.is_finite() {
+            break;
+        }
+        let primary_position = bodies[request.primary].position;
+        prediction.points.push(position);
+        prediction.primary_path.push(primary_position);
+        for (path, body) in prediction.body_paths.iter_mut().zip(&bodies) {
+            path.push(body.position);
+        }
+
+        let distance = (position - primary_position).length();
+        if let Some(previous) = previous_distance {
+            let now_falling = distance < previous;
+            if let Some(was_falling) = falling {
+                let marker = Marker {
+                    time: time - interval,
+                    position: prediction.points[i - 1],
+                    primary_position: prediction.primary_path[i - 1],
+                    distance: previous,
+                };
+                if was_falling && !now_falling && prediction.periapsis.is_none() {
+                    prediction.periapsis = Some(marker);
+                }
+                if !was_falling && now_falling && prediction.apoapsis.is_none() {
+                    prediction.apoapsis = Some(marker);
+                }
+            }
+            falling = Some(now_falling);
+        }
+        previous_distance = Some(distance);
+
+        if let Some(target) = request.target {
+            let separation = (position - bodies[target].position).length();
+            let closer = prediction
+                .closest_approach
+                .is_none_or(|m| separation < m.distance);
+            if closer {
+                prediction.closest_approach = Some(Marker {
+                    time,
+                    position,
+                    primary_position,
+                    distance: separation,
+                });
+                prediction.target_position = Some(bodies[target].position);
+            }
+        }
+
+        // the starting point may sit on the ground, anything after it has hit it
+        let impact = bodies[..craft_index]
+            .iter()
+            .any(|b| (position - b.position).length() < b.radius);
+        if i > 0 && impact {
+            break;
+        }
+    }
+    prediction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a craft dropped from twice the radius of an earth-sized planet
+    #[test]
+    fn stops_at_the_surface() {
+        let mut planet = Body::new(Vec2::ZERO, Vec2::ZERO, 5.9742e24);
+        planet.radius = 6.371e6;
+        let system = System::new(vec![planet]);
+        let craft = Body::new(Vec2::new(2.0 * planet.radius, 0.0), Vec2::ZERO, 0.0);
+        let request = PredictionRequest {
+            horizon: 86400.0,
+            samples: 1000,
+            primary: 0,
+            target: None,
+            include_bodies: false,
+            maneuvers: Vec::new(),
+        };
+        let prediction = predict(&system, &craft, &request);
+        let last = prediction.points.last().unwrap().length();
+        assert!(prediction.points.len() < request.samples);
+        assert!(last < planet.radius && last > 0.9 * planet.radius);
+    }
+}
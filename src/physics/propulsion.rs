@@ -0,0 +1,205 @@
+// rocket engines and propellant: mass flow from thrust and specific impulse, staging,
+// and the delta-v left according to the rocket equation
+use borsh::{BorshDeserialize, BorshSerialize};
+
+// standard gravity, converts specific impulse in seconds to exhaust velocity (m/s^2)
+pub const STANDARD_GRAVITY: f64 = 9.80665;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Stage {
+    pub dry_mass: f64,   // kg, structure and engines
+    pub propellant: f64, // kg left in the tanks
+    pub thrust: f64,     // N at full throttle
+    pub isp: f64,        // s
+}
+
+impl Stage {
+    pub fn new(dry_mass: f64, propellant: f64, thrust: f64, isp: f64) -> Self {
+        Self {
+            dry_mass,
+            propellant,
+            thrust,
+            isp,
+        }
+    }
+
+    pub fn wet_mass(&self) -> f64 {
+        self.dry_mass + self.propellant
+    }
+
+    // m/s
+    pub fn exhaust_velocity(&self) -> f64 {
+        self.isp * STANDARD_GRAVITY
+    }
+
+    // kg/s at full throttle
+    pub fn mass_flow(&self) -> f64 {
+        self.thrust / self.exhaust_velocity()
+    }
+
+    // seconds of full-throttle burn left
+    pub fn burn_time(&self) -> f64 {
+        self.propellant / self.mass_flow()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.propellant <= 0.0
+    }
+}
+
+// a stack of stages on top of a payload; `stages[0]` is the one firing
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Propulsion {
+    pub stages: Vec<Stage>,
+    pub payload: f64, // kg carried above the last stage
+}
+
+// what a burn did
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Burn {
+    pub delta_v: f64,     // m/s gained
+    pub duration: f64,    // s the engine actually ran
+    pub separated: usize, // stages dropped because they ran dry
+}
+
+impl Propulsion {
+    pub fn new(stages: Vec<Stage>, payload: f64) -> Self {
+        Self { stages, payload }
+    }
+
+    // current total mass, kg
+    pub fn mass(&self) -> f64 {
+        self.payload + self.stages.iter().map(Stage::wet_mass).sum::<f64>()
+    }
+
+    pub fn active(&self) -> Option<&Stage> {
+        self.stages.first()
+    }
+
+    // thrust divided by weight at standard gravity
+    pub fn thrust_to_weight(&self) -> f64 {
+        self.active()
+            .map_or(0.0, |stage| stage.thrust / (self.mass() * STANDARD_GRAVITY))
+    }
+
+    // fire the active stage at `throttle` (0..1) for up to `dt` seconds. propellant runs
+    // out mid-burn rather than going negative, and an empty stage is dropped so the next
+    // one fires on the following burn.
+    pub fn burn(&mut self, throttle: f64, dt: f64) -> Burn {
+        let mut burn = Burn::default();
+        let throttle = throttle.clamp(0.0, 1.0);
+        if throttle == 0.0 || dt <= 0.0 {
+            return burn;
+        }
+        let mass = self.mass();
+        let Some(stage) = self.stages.first_mut() else {
+            return burn;
+        };
+
+        let flow = stage.mass_flow() * throttle;
+        let burned = (flow * dt).min(stage.propellant);
+        stage.propellant -= burned;
+        burn.duration = burned / flow;
+        burn.delta_v = stage.exhaust_velocity() * (mass / (mass - burned)).ln();
+
+        if stage.is_empty() {
+            self.stages.remove(0);
+            burn.separated += 1;
+        }
+        burn
+    }
+
+    // seconds of full-thrust burning to gain `delta_v`, staging as needed; `None` when the
+    // stages do not hold that much
+    pub fn burn_time_for(&self, delta_v: f64) -> Option<f64> {
+        let mut mass = self.mass();
+        let mut remaining = delta_v;
+        let mut time = 0.0;
+        for stage in &self.stages {
+            if stage.propellant > 0.0 {
+                let ve = stage.exhaust_velocity();
+                let available = ve * (mass / (mass - stage.propellant)).ln();
+                if remaining <= available {
+                    let burned = mass * (1.0 - (-remaining / ve).exp());
+                    return Some(time + burned / stage.mass_flow());
+                }
+                remaining -= available;
+                time += stage.burn_time();
+            }
+            mass -= stage.wet_mass();
+        }
+        (remaining <= 0.0).then_some(time)
+    }
+
+    // delta-v left in all stages, firing them in order (tsiolkovsky: ve ln(m0 / m1))
+    pub fn delta_v(&self) -> f64 {
+        let mut mass = self.mass();
+        let mut total = 0.0;
+        for stage in &self.stages {
+            if stage.propellant > 0.0 {
+                total += stage.exhaust_velocity() * (mass / (mass - stage.propellant)).ln();
+            }
+            mass -= stage.wet_mass();
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a two stage rocket with a 1 t payload
+    fn two_stage() -> Propulsion {
+        Propulsion::new(
+            vec![
+                Stage::new(20_000.0, 200_000.0, 3.0e6, 300.0),
+                Stage::new(4_000.0, 40_000.0, 5.0e5, 340.0),
+            ],
+            1_000.0,
+        )
+    }
+
+    #[test]
+    fn delta_v_follows_the_rocket_equation() {
+        let rocket = two_stage();
+        let first = 300.0 * STANDARD_GRAVITY * (265_000.0_f64 / 65_000.0).ln();
+        let second = 340.0 * STANDARD_GRAVITY * (45_000.0_f64 / 5_000.0).ln();
+        assert!((rocket.delta_v() - (first + second)).abs() < 1.0e-6);
+
+        // burning in many small steps gains the same
+        let mut burning = two_stage();
+        let mut gained = 0.0;
+        for _ in 0..10_000 {
+            gained += burning.burn(1.0, 0.1).delta_v;
+        }
+        assert!((gained - (first + second)).abs() < 1.0e-6, "{gained}");
+        assert!(burning.stages.is_empty());
+        assert_eq!(burning.mass(), 1_000.0);
+    }
+
+    #[test]
+    fn empty_stage_drops_and_the_next_one_fires() {
+        let mut rocket = two_stage();
+        let burn_time = rocket.stages[0].burn_time();
+        let burn = rocket.burn(1.0, burn_time + 10.0);
+        assert_eq!(burn.separated, 1);
+        assert!((burn.duration - burn_time).abs() < 1.0e-9);
+        assert_eq!(rocket.stages.len(), 1);
+        assert_eq!(rocket.mass(), 45_000.0);
+        assert_eq!(rocket.active().unwrap().thrust, 5.0e5);
+
+        // the next burn uses the upper stage
+        let burn = rocket.burn(1.0, 1.0);
+        assert_eq!(burn.separated, 0);
+        assert!((rocket.stages[0].propellant - (40_000.0 - rocket.stages[0].mass_flow())).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn burn_time_for_stages_through() {
+        let rocket = two_stage();
+        assert_eq!(rocket.burn_time_for(rocket.delta_v() + 1.0), None);
+        let all = rocket.stages.iter().map(Stage::burn_time).sum::<f64>();
+        assert!((rocket.burn_time_for(rocket.delta_v()).unwrap() - all).abs() < 1.0e-6);
+    }
+}
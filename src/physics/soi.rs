@@ -0,0 +1,97 @@
+// spheres of influence: the region around a body inside which a craft is better
+// described as orbiting that body than the body's own primary (laplace's radius)
+use super::{Body, OrbitalElements, System, Vec2, G};
+
+impl System {
+    // radius of the sphere of influence of `body` around `parent`, a (m / M)^(2/5).
+    // an unbound body uses its current distance instead of the semi-major axis.
+    pub fn sphere_of_influence(&self, body: usize, parent: usize) -> f64 {
+        if body == parent {
+            return f64::INFINITY;
+        }
+        let orbit = self.elements(&self.bodies[body], parent);
+        let a = if orbit.is_bound() {
+            orbit.semi_major_axis
+        } else {
+            self.distance(body, parent)
+        };
+        a * (self.bodies[body].mass / self.bodies[parent].mass).powf(0.4)
+    }
+
+    // the body whose sphere of influence contains `at`: the smallest one when several
+    // overlap, `root` (usually the sun) when there is none
+    pub fn primary_at(&self, at: Vec2, root: usize) -> usize {
+        self.bodies
+            .iter()
+            .enumerate()
+            .filter(|&(i, body)| i != root && body.mass > 0.0)
+            .map(|(i, body)| (i, (body.position - at).length(), self.sphere_of_influence(i, root)))
+            .filter(|&(_, distance, radius)| distance < radius)
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map_or(root, |(i, _, _)| i)
+    }
+}
+
+// analytic two-body motion of `craft` around `primary` for `dt` seconds, treating the
+// primary as fixed; `None` for degenerate (radial or parabolic) orbits, which the caller
+// has to integrate numerically instead
+pub fn coast(craft: &Body, primary: &Body, dt: f64) -> Option<Body> {
+    let mu = G * (primary.mass + craft.mass);
+    let orbit = OrbitalElements::from_state(
+        craft.position - primary.position,
+        craft.velocity - primary.velocity,
+        mu,
+    );
+    let (position, velocity) = orbit.propagate(dt, mu).to_state(mu);
+    if !position.is_finite() || !velocity.is_finite() || (orbit.eccentricity - 1.0).abs() < 1.0e-6 {
+        return None;
+    }
+    Some(Body {
+        position: primary.position + position,
+        velocity: primary.velocity + velocity,
+        ..*craft
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::testing::earth_and_mars;
+
+    #[test]
+    fn primary_switches_at_the_edge_of_earths_sphere() {
+        let system = earth_and_mars();
+        let (sun, earth) = (0, 1);
+        let radius = system.sphere_of_influence(earth, sun);
+        // laplace's radius of the earth is about 925,000 km
+        assert!((radius / 9.25e8 - 1.0).abs() < 0.01, "{radius}");
+
+        let outward = (system.bodies[earth].position - system.bodies[sun].position).normalized();
+        let at = |distance: f64| system.bodies[earth].position + outward * distance;
+        assert_eq!(system.primary_at(at(0.99 * radius), sun), earth);
+        assert_eq!(system.primary_at(at(1.01 * radius), sun), sun);
+    }
+
+    #[test]
+    fn coast_agrees_with_the_integrator_over_an_orbit() {
+        let earth = Body::new(Vec2::ZERO, Vec2::ZERO, 5.9742e24);
+        let start = Body::new(Vec2::new(7.0e6, 0.0), Vec2::new(0.0, 8.5e3), 0.0);
+        let mu = G * earth.mass;
+        let period = OrbitalElements::from_state(start.position, start.velocity, mu).period;
+
+        let mut system = System::new(vec![earth]);
+        let mut craft = [start];
+        let frames = 100;
+        for fraction in [0.5, 1.0] {
+            for _ in 0..frames {
+                system.advance_with_craft(period / (2 * frames) as f64, &mut craft, &[Vec2::ZERO]);
+            }
+            let coasted = coast(&start, &earth, fraction * period).unwrap();
+            assert!((coasted.position - craft[0].position).length() < 10.0);
+            assert!((coasted.velocity - craft[0].velocity).length() < 0.01);
+        }
+        // one period brings it back to the start
+        let coasted = coast(&start, &earth, period).unwrap();
+        assert!((coasted.position - start.position).length() < 1.0);
+    }
+}
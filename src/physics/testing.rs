@@ -0,0 +1,16 @@
+// fixtures shared by the physics tests
+use super::ephemeris::{julian_date, place_at_epoch};
+use super::{Body, System, Vec2};
+
+pub const DAY: f64 = 86400.0;
+
+// sun, earth and mars where the standish elements put them on 2026-01-01
+pub fn earth_and_mars() -> System {
+    let mut system = System::new(vec![
+        Body::new(Vec2::ZERO, Vec2::ZERO, 1.98892e30),
+        Body::new(Vec2::ZERO, Vec2::ZERO, 5.9742e24),
+        Body::new(Vec2::ZERO, Vec2::ZERO, 6.39e23),
+    ]);
+    place_at_epoch(&mut system, &["Sun", "Earth", "Mars"], 0, julian_date(2026, 1, 1));
+    system
+}
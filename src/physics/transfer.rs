@@ -0,0 +1,130 @@
+// hohmann transfers between two planets around the same star, and when to leave for one.
+// the planets' orbits are taken as circles at their current distance, which is what the
+// textbook transfer assumes; the launch window then falls out of the phase angle.
+use std::f64::consts::PI;
+
+use super::kepler::normalize_angle;
+use super::{System, Vec2, G};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hohmann {
+    pub departure_delta_v: f64, // m/s on top of the origin's orbital speed (v infinity)
+    pub arrival_delta_v: f64,   // m/s to match the target's orbital speed
+    pub time_of_flight: f64,    // s, half the transfer ellipse
+    pub phase_angle: f64,       // rad the target has to lead the origin by at departure
+}
+
+// transfer from a circular orbit of radius `r1` to one of `r2` around a body with
+// gravitational parameter `mu`, works inwards as well as outwards
+pub fn hohmann(mu: f64, r1: f64, r2: f64) -> Hohmann {
+    let a = (r1 + r2) / 2.0;
+    let vis_viva = |r: f64| (mu * (2.0 / r - 1.0 / a)).sqrt();
+    let time_of_flight = PI * (a.powi(3) / mu).sqrt();
+    let target_motion = (mu / r2.powi(3)).sqrt();
+    Hohmann {
+        departure_delta_v: vis_viva(r1) - (mu / r1).sqrt(),
+        arrival_delta_v: (mu / r2).sqrt() - vis_viva(r2),
+        time_of_flight,
+        phase_angle: normalize_angle(PI - target_motion * time_of_flight),
+    }
+}
+
+// burn from a circular parking orbit of `radius` around a planet with gravitational
+// parameter `mu` that leaves the planet with `excess_speed` (oberth effect included)
+pub fn departure_burn(mu: f64, radius: f64, excess_speed: f64) -> f64 {
+    (excess_speed * excess_speed + 2.0 * mu / radius).sqrt() - (mu / radius).sqrt()
+}
+
+// world angle, seen from the planet, of the periapsis of an escape hyperbola from a
+// parking orbit of `radius` that leaves with `excess_velocity` (relative to the planet).
+// burning there, along the orbit, sends the craft off in that direction.
+pub fn escape_periapsis_angle(mu: f64, radius: f64, excess_velocity: Vec2, retrograde: bool) -> f64 {
+    let eccentricity = 1.0 + radius * excess_velocity.length_squared() / mu;
+    // the outgoing asymptote is this far past periapsis, in the direction of motion
+    let asymptote = (-1.0 / eccentricity).acos();
+    let sense = if retrograde { -1.0 } else { 1.0 };
+    normalize_angle(excess_velocity.angle() - sense * asymptote)
+}
+
+// offset from a planet, across the approach, to aim at so that a craft arriving with
+// `excess_velocity` (relative to the planet) swings by at `periapsis` from its center,
+// going counter-clockwise. the hyperbola's impact parameter, gravity focusing included.
+pub fn impact_offset(mu: f64, periapsis: f64, excess_velocity: Vec2) -> Vec2 {
+    let focusing = 2.0 * mu / (periapsis * excess_velocity.length_squared());
+    -excess_velocity.normalized().perp() * periapsis * (1.0 + focusing).sqrt()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LaunchWindow {
+    pub transfer: Hohmann,
+    pub phase_angle: f64,    // rad the target leads the origin by now
+    pub wait: f64,           // s until the phase angle is right
+    pub synodic_period: f64, // s between two windows
+}
+
+// the next hohmann window from body `origin` to body `target`, both orbiting `center`
+// in the same direction; `None` if either is not on a bound orbit
+pub fn launch_window(system: &System, origin: usize, target: usize, center: usize) -> Option<LaunchWindow> {
+    let sun = system.bodies[center];
+    let from = system.bodies[origin];
+    let to = system.bodies[target];
+    let origin_orbit = system.elements(&from, center);
+    let target_orbit = system.elements(&to, center);
+    if !origin_orbit.is_bound()
+        || !target_orbit.is_bound()
+        || origin_orbit.is_retrograde() != target_orbit.is_retrograde()
+    {
+        return None;
+    }
+
+    let r1 = (from.position - sun.position).length();
+    let r2 = (to.position - sun.position).length();
+    let transfer = hohmann(G * sun.mass, r1, r2);
+
+    // angles grow in the direction of motion
+    let sense = if origin_orbit.is_retrograde() { -1.0 } else { 1.0 };
+    let phase_angle = normalize_angle(sense * ((to.position - sun.position).angle() - (from.position - sun.position).angle()));
+    // the phase angle changes at the difference of the mean motions
+    let rate = 2.0 * PI / target_orbit.period - 2.0 * PI / origin_orbit.period;
+    let synodic_period = (2.0 * PI / rate).abs();
+    let wait = (normalize_angle(transfer.phase_angle - phase_angle) / rate).rem_euclid(synodic_period);
+
+    Some(LaunchWindow {
+        transfer,
+        phase_angle,
+        wait,
+        synodic_period,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::ephemeris::{format_date, julian_date};
+    use crate::physics::testing::{earth_and_mars, DAY};
+    use crate::physics::AU;
+
+    #[test]
+    fn earth_to_mars_hohmann() {
+        let transfer = hohmann(G * 1.98892e30, AU, 1.524 * AU);
+        // the textbook figures: 2.9 km/s out, 2.6 km/s in, 259 days, mars 44 deg ahead
+        assert!((transfer.departure_delta_v - 2.94e3).abs() < 20.0);
+        assert!((transfer.arrival_delta_v - 2.65e3).abs() < 20.0);
+        assert!((transfer.time_of_flight / DAY - 259.0).abs() < 1.0);
+        assert!((transfer.phase_angle.to_degrees() - 44.3).abs() < 0.5);
+    }
+
+    #[test]
+    fn next_mars_window_is_late_2026() {
+        let system = earth_and_mars();
+        let window = launch_window(&system, 1, 2, 0).unwrap();
+        let departure = julian_date(2026, 1, 1) + window.wait / DAY;
+        // the 2026 opportunity opens around the start of november
+        assert!(
+            (julian_date(2026, 10, 15)..julian_date(2026, 12, 1)).contains(&departure),
+            "{}",
+            format_date(departure)
+        );
+        assert!((window.synodic_period / DAY - 780.0).abs() < 10.0);
+    }
+}
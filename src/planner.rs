@@ -0,0 +1,240 @@
+// maneuver planning on the game screen: burns placed on the predicted path with the
+// mouse, delta-v handles around the selected one and the countdown to the next burn
+use borsh::{BorshDeserialize, BorshSerialize};
+use turbo::prelude::*;
+
+use crate::camera::Camera;
+use crate::hud::{draw_panel, format_duration};
+use crate::physics::{Maneuver, NodeMarker, Propulsion, Vec2};
+use crate::trajectory;
+
+// how close to a node, handle or the path a click has to land, in pixels
+const PICK_PIXELS: f64 = 8.0;
+
+// distance of the handles from their node, in pixels
+const HANDLE_PIXELS: f64 = 30.0;
+
+// delta-v change per frame while a handle is held: starts fine, grows the longer it is held
+const HANDLE_RATE: f64 = 0.1;
+const HANDLE_GROWTH: f64 = 1.08;
+const MAX_HANDLE_RATE: f64 = 100.0;
+
+const NODE_COLOR: u32 = 0xFFAA_44FF;
+const PROGRADE_COLOR: u32 = 0xFFEE_44FF;
+const RADIAL_COLOR: u32 = 0x44CC_FFFF;
+
+#[derive(Clone, Copy, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum Handle {
+    Prograde,
+    Retrograde,
+    RadialOut,
+    RadialIn,
+}
+
+const HANDLES: [Handle; 4] = [Handle::Prograde, Handle::Retrograde, Handle::RadialOut, Handle::RadialIn];
+
+impl Handle {
+    // screen direction of the handle around a node
+    fn direction(self, node: &NodeMarker) -> Vec2 {
+        match self {
+            Handle::Prograde => node.prograde,
+            Handle::Retrograde => -node.prograde,
+            Handle::RadialOut => node.radial,
+            Handle::RadialIn => -node.radial,
+        }
+    }
+
+    fn color(self) -> u32 {
+        match self {
+            Handle::Prograde | Handle::Retrograde => PROGRADE_COLOR,
+            Handle::RadialOut | Handle::RadialIn => RADIAL_COLOR,
+        }
+    }
+
+    fn apply(self, maneuver: &mut Maneuver, amount: f64) {
+        match self {
+            Handle::Prograde => maneuver.prograde += amount,
+            Handle::Retrograde => maneuver.prograde -= amount,
+            Handle::RadialOut => maneuver.radial += amount,
+            Handle::RadialIn => maneuver.radial -= amount,
+        }
+    }
+}
+
+// the planned burns, in time order, and the one being edited
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Planner {
+    pub maneuvers: Vec<Maneuver>,
+    pub selected: Option<usize>,
+    held: Option<(Handle, u32)>, // handle being pressed and for how many frames
+}
+
+impl Planner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // the soonest burn
+    pub fn next(&self) -> Option<&Maneuver> {
+        self.maneuvers.first()
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.maneuvers.remove(index);
+        self.selected = match self.selected {
+            Some(s) if s == index => None,
+            Some(s) if s > index => Some(s - 1),
+            selected => selected,
+        };
+        self.held = None;
+    }
+
+    pub fn add(&mut self, maneuver: Maneuver) {
+        let index = self.maneuvers.partition_point(|m| m.time <= maneuver.time);
+        self.maneuvers.insert(index, maneuver);
+        self.selected = Some(index);
+        self.held = None;
+    }
+
+    // drop burns whose window is over: half the burn time past the node
+    pub fn expire(&mut self, now: f64, propulsion: &Propulsion) {
+        while let Some(next) = self.next() {
+            let half_burn = propulsion.burn_time_for(next.delta_v()).unwrap_or(0.0) / 2.0;
+            if now <= next.time + half_burn {
+                break;
+            }
+            self.remove(0);
+        }
+    }
+
+    // seconds until the next burn has to start so that it is centered on its node,
+    // negative once it should be running
+    pub fn until_burn(&self, now: f64, propulsion: &Propulsion) -> Option<f64> {
+        let next = self.next()?;
+        let half_burn = propulsion.burn_time_for(next.delta_v()).unwrap_or(0.0) / 2.0;
+        Some(next.time - half_burn - now)
+    }
+
+    // mouse input: RIGHT click on the path adds a burn there, on a burn removes it; LEFT
+    // click selects a burn and holding a handle of the selected one changes its delta-v.
+    // `primary_now` anchors the predicted path like `trajectory::draw`.
+    pub fn update(&mut self, camera: &Camera, primary_now: Vec2, now: f64) {
+        let m = mouse(0);
+        let cursor = Vec2::new(m.position[0] as f64, m.position[1] as f64);
+        let screen = |p: Vec2, primary: Vec2| {
+            let (x, y) = camera.to_screen(primary_now + (p - primary));
+            Vec2::new(x as f64, y as f64)
+        };
+        let near = |p: Vec2| (p - cursor).length() <= PICK_PIXELS;
+
+        let picked = trajectory::with_prediction(|prediction| {
+            let nodes: Vec<Vec2> = prediction
+                .nodes
+                .iter()
+                .map(|n| screen(n.marker.position, n.marker.primary_position))
+                .collect();
+            let node = nodes.iter().position(|&p| near(p));
+            let handle = self.selected.and_then(|s| {
+                let center = *nodes.get(s)?;
+                let node = prediction.nodes.get(s)?;
+                HANDLES
+                    .into_iter()
+                    .find(|h| near(center + h.direction(node) * HANDLE_PIXELS))
+            });
+            // nearest point of the path, as seconds from now
+            let on_path = prediction
+                .points
+                .iter()
+                .zip(&prediction.primary_path)
+                .enumerate()
+                .map(|(i, (&p, &primary))| (i, (screen(p, primary) - cursor).length()))
+                .filter(|&(_, d)| d <= PICK_PIXELS)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i as f64 * prediction.interval);
+            (node, handle, on_path)
+        });
+        let Some((node, handle, on_path)) = picked else {
+            self.held = None;
+            return;
+        };
+
+        if m.right.just_pressed() {
+            match (node, on_path) {
+                (Some(index), _) => self.remove(index),
+                (None, Some(after)) if after > 0.0 => self.add(Maneuver::new(now + after)),
+                _ => {}
+            }
+        }
+
+        if m.left.just_pressed() {
+            self.held = handle.map(|h| (h, 0));
+            if self.held.is_none() && node.is_some() {
+                self.selected = node;
+            }
+        }
+        if !m.left.pressed() {
+            self.held = None;
+        }
+        if let (Some((handle, frames)), Some(selected)) = (self.held, self.selected) {
+            let rate = (HANDLE_RATE * HANDLE_GROWTH.powi(frames as i32)).min(MAX_HANDLE_RATE);
+            if let Some(maneuver) = self.maneuvers.get_mut(selected) {
+                handle.apply(maneuver, rate);
+            }
+            self.held = Some((handle, frames + 1));
+        }
+    }
+
+    // the burns on the predicted path, with handles around the selected one
+    pub fn draw(&self, camera: &Camera, primary_now: Vec2) {
+        trajectory::with_prediction(|prediction| {
+            for (i, node) in prediction.nodes.iter().enumerate() {
+                let center = primary_now + (node.marker.position - node.marker.primary_position);
+                let (x, y) = camera.to_screen(center);
+                circ!(d = 10, x = x - 5, y = y - 5, color = NODE_COLOR);
+                if self.selected != Some(i) {
+                    continue;
+                }
+                for handle in HANDLES {
+                    let offset = handle.direction(node) * HANDLE_PIXELS;
+                    let (hx, hy) = (x + offset.x as i32, y + offset.y as i32);
+                    path!(start = (x, y), end = (hx, hy), color = handle.color(), width = 1);
+                    circ!(d = 8, x = hx - 4, y = hy - 4, color = handle.color());
+                }
+            }
+        });
+    }
+}
+
+// the next burn: its delta-v, how long it takes and when to light the engine
+pub fn draw_countdown(planner: &Planner, propulsion: &Propulsion, now: f64, top: i32) -> i32 {
+    let mut lines = Vec::new();
+    match planner.next() {
+        Some(next) => {
+            lines.push(format!(
+                "next burn: {:+.1} prograde {:+.1} radial, {:.1} m/s",
+                next.prograde,
+                next.radial,
+                next.delta_v()
+            ));
+            match propulsion.burn_time_for(next.delta_v()) {
+                Some(burn_time) => {
+                    lines.push(format!("burn time: {}", format_duration(burn_time)));
+                    let until = next.time - burn_time / 2.0 - now;
+                    lines.push(if until > 0.0 {
+                        format!("start burn in: {}", format_duration(until))
+                    } else {
+                        format!("burn now, {} to go", format_duration(until + burn_time))
+                    });
+                }
+                None => lines.push(format!("not enough delta-v, {:.0} m/s left", propulsion.delta_v())),
+            }
+        }
+        None => lines.push("no burns planned".to_string()),
+    }
+    lines.push("RIGHT click the path to plan a burn".to_string());
+    draw_panel(&lines, top)
+}
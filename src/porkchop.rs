@@ -0,0 +1,216 @@
+// porkchop plot: departure date across, arrival date down, every cell coloured by the
+// delta-v of the lambert transfer between the two planets on those dates. computed once
+// when the screen opens and kept outside the saved state, like the predicted path.
+use std::cell::RefCell;
+
+use turbo::prelude::*;
+
+use crate::hud::format_duration;
+use crate::physics::ephemeris::format_date;
+use crate::physics::{coast, lambert, Body, System, Vec2, G};
+
+const COLUMNS: usize = 60;
+const ROWS: usize = 60;
+const CELL_PIXELS: i32 = 12;
+const PLOT_X: i32 = (1920 - COLUMNS as i32 * CELL_PIXELS) / 2;
+const PLOT_Y: i32 = 160;
+
+// departures over a bit more than one earth-mars synodic period, flights of 80 to 500 days
+const DEPARTURE_DAYS: f64 = 900.0;
+const MIN_FLIGHT_DAYS: f64 = 80.0;
+const MAX_FLIGHT_DAYS: f64 = 500.0;
+
+// colour scale: green at the cheapest cell, red this much above it
+const DELTA_V_RANGE: f64 = 10_000.0;
+
+const EMPTY_COLOR: u32 = 0x2020_20FF;
+const BEST_COLOR: u32 = 0xFFFF_FFFF;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transfer {
+    pub departure: f64,         // simulation time of departure
+    pub arrival: f64,           // simulation time of arrival
+    pub excess_velocity: Vec2,  // departure velocity relative to the origin planet (v infinity)
+    pub delta_v: f64,           // v infinity at departure plus at arrival
+}
+
+struct Plot {
+    start_jd: f64, // julian date of simulation time zero
+    departure_start: f64,
+    arrival_start: f64,
+    step: f64,                      // seconds per cell, both axes
+    cells: Vec<Option<Transfer>>, // ROWS x COLUMNS, arrival rows
+    best: Option<usize>,
+}
+
+thread_local! {
+    static PLOT: RefCell<Option<Plot>> = const { RefCell::new(None) };
+}
+
+// fill the plot for transfers from body `origin` to `target` around `center`, starting
+// now. the planets follow kepler orbits from their current simulated states.
+pub fn compute(system: &System, origin: usize, target: usize, center: usize, start_jd: f64) {
+    let sun = system.bodies[center];
+    let mu = G * sun.mass;
+    let step = (DEPARTURE_DAYS / COLUMNS as f64) * 86400.0;
+    let departure_start = system.time;
+    let arrival_start = departure_start + MIN_FLIGHT_DAYS * 86400.0;
+
+    // planet state relative to the sun, `dt` seconds from now
+    let state = |body: usize, dt: f64| {
+        let relative = Body {
+            position: system.bodies[body].position - sun.position,
+            velocity: system.bodies[body].velocity - sun.velocity,
+            ..system.bodies[body]
+        };
+        let center = Body {
+            position: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            ..sun
+        };
+        coast(&relative, &center, dt)
+    };
+    let departures: Vec<Option<Body>> = (0..COLUMNS).map(|c| state(origin, c as f64 * step)).collect();
+    let arrivals: Vec<Option<Body>> = (0..ROWS)
+        .map(|r| state(target, arrival_start - departure_start + r as f64 * step))
+        .collect();
+
+    let flights = MIN_FLIGHT_DAYS * 86400.0..=MAX_FLIGHT_DAYS * 86400.0;
+    let mut cells = Vec::with_capacity(ROWS * COLUMNS);
+    for (row, to) in arrivals.iter().enumerate() {
+        for (column, from) in departures.iter().enumerate() {
+            let departure = departure_start + column as f64 * step;
+            let arrival = arrival_start + row as f64 * step;
+            let flight = arrival - departure;
+            let transfer = match (from, to) {
+                (Some(from), Some(to)) if flights.contains(&flight) => {
+                    lambert(mu, from.position, to.position, flight, true).map(|(leave, arrive)| Transfer {
+                        departure,
+                        arrival,
+                        excess_velocity: leave - from.velocity,
+                        delta_v: (leave - from.velocity).length() + (arrive - to.velocity).length(),
+                    })
+                }
+                _ => None,
+            };
+            cells.push(transfer);
+        }
+    }
+    let best = cells
+        .iter()
+        .enumerate()
+        .filter_map(|(i, cell)| cell.map(|t| (i, t.delta_v)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i);
+
+    PLOT.with(|plot| {
+        *plot.borrow_mut() = Some(Plot {
+            start_jd,
+            departure_start,
+            arrival_start,
+            step,
+            cells,
+            best,
+        })
+    });
+}
+
+pub fn clear() {
+    PLOT.with(|plot| *plot.borrow_mut() = None);
+}
+
+// cell under a screen point
+fn cell_at(x: i32, y: i32) -> Option<usize> {
+    let column = (x - PLOT_X).div_euclid(CELL_PIXELS);
+    let row = (y - PLOT_Y).div_euclid(CELL_PIXELS);
+    let inside = (0..COLUMNS as i32).contains(&column) && (0..ROWS as i32).contains(&row);
+    inside.then(|| row as usize * COLUMNS + column as usize)
+}
+
+// green for the cheapest transfer through yellow to red
+fn color(delta_v: f64, best: f64) -> u32 {
+    let t = ((delta_v - best) / DELTA_V_RANGE).clamp(0.0, 1.0);
+    let red = (255.0 * (2.0 * t).min(1.0)) as u32;
+    let green = (255.0 * (2.0 * (1.0 - t)).min(1.0)) as u32;
+    (red << 24) | (green << 16) | 0xFF
+}
+
+pub fn draw(origin: &str, target: &str) {
+    clear!(0x0000_00FF);
+    PLOT.with(|plot| {
+        let plot = plot.borrow();
+        let Some(plot) = plot.as_ref() else {
+            return;
+        };
+        let date = |time: f64| format_date(plot.start_jd + time / 86400.0);
+
+        text!(
+            &format!("porkchop plot {origin} -> {target}: departure across, arrival down, delta-v as colour"),
+            x = PLOT_X,
+            y = PLOT_Y - 40,
+            font = Font::M,
+        );
+        text!(
+            "click a transfer to plan its departure burn, B to go back",
+            x = PLOT_X,
+            y = PLOT_Y - 24,
+            font = Font::S,
+        );
+
+        let best = plot.best.and_then(|i| plot.cells[i]).map_or(0.0, |t| t.delta_v);
+        for (i, cell) in plot.cells.iter().enumerate() {
+            let x = PLOT_X + (i % COLUMNS) as i32 * CELL_PIXELS;
+            let y = PLOT_Y + (i / COLUMNS) as i32 * CELL_PIXELS;
+            let color = cell.map_or(EMPTY_COLOR, |t| color(t.delta_v, best));
+            rect!(x = x, y = y, w = CELL_PIXELS, h = CELL_PIXELS, color = color);
+        }
+        if let Some(i) = plot.best {
+            let x = PLOT_X + (i % COLUMNS) as i32 * CELL_PIXELS;
+            let y = PLOT_Y + (i / COLUMNS) as i32 * CELL_PIXELS;
+            circ!(d = CELL_PIXELS, x = x, y = y, color = BEST_COLOR);
+        }
+
+        // a date every ten cells along both axes
+        let bottom = PLOT_Y + ROWS as i32 * CELL_PIXELS;
+        for column in (0..COLUMNS).step_by(10) {
+            let x = PLOT_X + column as i32 * CELL_PIXELS;
+            let label = date(plot.departure_start + column as f64 * plot.step);
+            text!(&label, x = x, y = bottom + 6, font = Font::S);
+        }
+        for row in (0..ROWS).step_by(10) {
+            let y = PLOT_Y + row as i32 * CELL_PIXELS;
+            let label = date(plot.arrival_start + row as f64 * plot.step);
+            text!(&label, x = PLOT_X - 70, y = y, font = Font::S);
+        }
+
+        let m = mouse(0);
+        let hovered = cell_at(m.position[0], m.position[1]).and_then(|i| plot.cells[i]);
+        let shown = hovered.or_else(|| plot.best.and_then(|i| plot.cells[i]));
+        if let Some(transfer) = shown {
+            let label = if hovered.is_some() { "transfer" } else { "cheapest" };
+            text!(
+                &format!(
+                    "{label}: leave {} arrive {} ({}), {:.0} m/s ({:.0} m/s at departure)",
+                    date(transfer.departure),
+                    date(transfer.arrival),
+                    format_duration(transfer.arrival - transfer.departure),
+                    transfer.delta_v,
+                    transfer.excess_velocity.length()
+                ),
+                x = PLOT_X,
+                y = bottom + 24,
+                font = Font::M,
+            );
+        }
+    });
+}
+
+// the transfer clicked this frame
+pub fn clicked() -> Option<Transfer> {
+    let m = mouse(0);
+    if !m.left.just_pressed() {
+        return None;
+    }
+    let i = cell_at(m.position[0], m.position[1])?;
+    PLOT.with(|plot| plot.borrow().as_ref().and_then(|plot| plot.cells[i]))
+}
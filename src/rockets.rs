@@ -1,113 +1,194 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
-use serde::{Deserialize, Serialize};
-use serde_json::Result;
 use borsh::{BorshDeserialize, BorshSerialize};
 use turbo::prelude::*;
 use std::f64::consts::PI;
 
-use crate::planet::Planet;
-use crate::{G, SOFTENING_FACTOR, AU, SCALE, TIMESTEP};
-
-// Constants
-const GRAVITY: f64 = 9.8;
-const LAUNCH_POWER_INCREASE: f64 = 1.0;
-const MAX_LAUNCH_POWER: f64 = 1.0;
-const PLANET_RADIUS: f64 = 50.0;
-// width and height, duh
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1080;
-
-#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
-pub struct Rocket {
-    name: String,
-    manufacturer: String,
-    height: f64,
-    diameter: f64,
-    mass: f64,
-    thrust: f64,
-    fuel_capacity: f64,
-    max_speed: f64,
-    max_altitude: f64,
-    stages: u8,
-    payload_capacity: f64,
-    reliability: f64,
-    cost: u64,
-    cooldown_time: u32,
-    price: u64,
-    image: String,
-    construction_speed: u32,
-    pub is_launching: bool,
-    pub x: f64,
-    pub y: f64,
-    pub velocity_x: f64,
-    pub velocity_y: f64,
-    pub rotation: f64,
-    launch_power: f64,
+use crate::camera::Camera;
+use crate::catalog::RocketSpec;
+use crate::physics::{
+    coast, drag_acceleration, dynamic_pressure, heat_flux, kepler, Body, Burn, Propulsion, System, Vec2, STANDARD_GRAVITY,
+};
+
+// whether the rocket is flying, resting on a body or wrecked
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum FlightStatus {
+    #[default]
+    Flying,
+    // longitude is the angle of the spot on the rotating ground, world angle at time zero
+    Landed { body: usize, longitude: f64 },
+    Crashed { body: usize },
+    // torn apart in flight by too much dynamic pressure, acceleration or speed, or burned up
+    BrokenUp,
 }
 
-impl Rocket {
-    fn thrust_to_weight_ratio(&self) -> f64 {
-        const GRAVITY: f64 = 9.81;
-        self.thrust / (self.mass * GRAVITY)
+impl FlightStatus {
+    // nothing left to fly
+    pub fn is_wrecked(&self) -> bool {
+        matches!(self, FlightStatus::Crashed { .. } | FlightStatus::BrokenUp)
     }
+}
 
-    pub fn new() -> Self {
-        Self {
-            name: "Falcon 9".to_string(),
-            manufacturer: "SpaceX".to_string(),
-            height: 70.0,
-            diameter: 3.7,
-            mass: 5490.54,
-            thrust: 7607000.0,
-            fuel_capacity: 287400.0,
-            max_speed: 7500.0,
-            max_altitude: 250000.0,
-            stages: 2,
-            payload_capacity: 22800.0,
-            reliability: 0.98,
-            cost: 62000000,
-            cooldown_time: 1209600,
-            price: 67000000,
-            image: "falcon9.png".to_string(),
-            construction_speed: 180,
-            x: 500.0, // Closer to origin
-            y: 500.0,
-            velocity_x: 0.0,
-            velocity_y: 0.0,
-            rotation: 0.0,
-            launch_power: 0.0,
-            is_launching: false,
+// where the nose points. the holds turn the rocket every step, relative to the primary
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum Attitude {
+    // wherever the player turned it
+    #[default]
+    Manual,
+    Prograde,
+    Retrograde,
+    RadialOut,
+    RadialIn,
+}
+
+impl Attitude {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attitude::Manual => "manual",
+            Attitude::Prograde => "prograde",
+            Attitude::Retrograde => "retrograde",
+            Attitude::RadialOut => "radial out",
+            Attitude::RadialIn => "radial in",
         }
     }
 
+    pub fn next(self) -> Self {
+        match self {
+            Attitude::Manual => Attitude::Prograde,
+            Attitude::Prograde => Attitude::Retrograde,
+            Attitude::Retrograde => Attitude::RadialOut,
+            Attitude::RadialOut => Attitude::RadialIn,
+            Attitude::RadialIn => Attitude::Manual,
+        }
+    }
+}
 
+// the shape and strength of the vehicle, copied from its catalog entry
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Airframe {
+    pub area: f64, // m^2 facing the air
+    pub drag_coefficient: f64,
+    pub max_q: f64,    // Pa
+    pub max_load: f64, // g
+    pub nose_radius: f64,   // m
+    pub max_heat_flux: f64, // W/m^2
+    pub max_heat_load: f64, // J/m^2
+    pub max_speed: f64,     // m/s through the air
+    pub max_altitude: f64,  // m, the rated ceiling; a hint, going higher breaks nothing
+}
 
+// what the air and the engines are doing to the vehicle
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Ascent {
+    pub altitude: f64,         // m above the primary's surface
+    pub dynamic_pressure: f64, // Pa
+    pub load: f64,             // thrust and drag in g
+    pub max_q: f64,            // highest dynamic pressure of the flight
+    pub max_q_altitude: f64,
+    pub heat_flux: f64,        // W/m^2 at the nose
+    pub peak_heat_flux: f64,   // highest heat flux of the flight
+    pub heat_load: f64,        // J/m^2 taken so far
+    pub airspeed: f64,         // m/s relative to the air, zero outside it
+}
 
+// why the vehicle broke up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Failure {
+    DynamicPressure(f64), // Pa
+    Overload(f64),        // g
+    Overheat(f64),        // W/m^2
+    HeatLoad(f64),        // J/m^2
+    Overspeed(f64),       // m/s
+}
 
-    pub fn draw(&self, scale: f64) {
-/*
-        let [canvas_width, canvas_height] = canvas_size!();
-    
-        let adjusted_scale = scale * 1e-3; // Increased from 1e-5
-        
-        let screen_x = ((self.x * adjusted_scale) + (canvas_width as f64 / 2.0)) as i32;
-        let screen_y = ((self.y * adjusted_scale) + (canvas_height as f64 / 2.0)) as i32;
-    
+// how the rocket moves while the engine is off
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub enum Propagation {
+    // integrated with the planets as a massless test particle, like a burn
+    #[default]
+    Numerical,
+    // kepler orbit around the primary, exact at any time warp
+    PatchedConic,
+}
 
-      //  let screen_x = screen_x.clamp(0, canvas_width as i32 - 1);
-      //  let screen_y = screen_y.clamp(0, canvas_height as i32 - 1);
+impl Propagation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Propagation::Numerical => "n-body",
+            Propagation::PatchedConic => "patched conics",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Propagation::Numerical => Propagation::PatchedConic,
+            Propagation::PatchedConic => Propagation::Numerical,
+        }
+    }
+}
+
+// one flight of a catalog rocket. the catalog entry (`RocketSpec`) stays untouched, so
+// the same spec can be launched again and again; everything here is flight state.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct RocketInstance {
+    spec_name: String, // catalog entry this flight was built from
+    pub engine_on: bool,
+    pub body: Body, // world-space state, integrated by the physics module
+    pub status: FlightStatus,
+    pub propulsion: Propulsion, // stages and propellant left, drives the mass
+    pub primary: usize, // body whose sphere of influence the rocket is in
+    pub propagation: Propagation,
+    pub airframe: Airframe,
+    pub ascent: Ascent,
+    pub rotation: f64, // world angle the nose and the thrust point at
+    pub attitude: Attitude,
+    pub throttle: f64, // 0..1
+}
+
+impl RocketInstance {
+    pub fn name(&self) -> &str {
+        &self.spec_name
+    }
+
+    // a fueled `spec` carrying `payload` kg, not placed anywhere yet (see `place_on_surface`)
+    pub fn from_spec(spec: &RocketSpec, payload: f64) -> Self {
+        let propulsion = spec.propulsion(payload);
+        Self {
+            spec_name: spec.name.clone(),
+            body: Body::new(Vec2::ZERO, Vec2::ZERO, propulsion.mass()),
+            status: FlightStatus::Flying,
+            propulsion,
+            primary: 0,
+            propagation: Propagation::default(),
+            airframe: Airframe {
+                area: spec.frontal_area(),
+                drag_coefficient: spec.drag_coefficient,
+                max_q: spec.limits.max_q_pa,
+                max_load: spec.limits.max_load_g,
+                nose_radius: spec.nose_radius(),
+                max_heat_flux: spec.limits.max_heat_flux_kw_m2 * 1000.0,
+                max_heat_load: spec.limits.max_heat_load_mj_m2 * 1.0e6,
+                max_speed: spec.limits.max_speed_m_s,
+                max_altitude: spec.limits.max_altitude_m,
+            },
+            ascent: Ascent::default(),
+            rotation: 0.0,
+            attitude: Attitude::Manual,
+            throttle: 1.0,
+            engine_on: false,
+        }
+    }
+
+    pub fn draw(&self, camera: &Camera) {
+        // world space to screen space, centered on the 32x64 sprite
+        let (screen_x, screen_y) = camera.to_screen(self.body.position);
+        let (screen_x, screen_y) = (screen_x - 16, screen_y - 32);
 
-        
-*/
 let rotation_degrees = (self.rotation * 180.0 / PI) as i32;
-        //log!("Screen position: ({}, {})", self.x, self.y);
+        //log!("Screen position: ({}, {})", screen_x, screen_y);
 
         sprite!(
             "falcon9",
-            x = self.x,
-            y = self.y,
+            x = screen_x,
+            y = screen_y,
             w = 64,
             h = 128,
             color = 0xFFFFFFFF,
@@ -121,119 +202,202 @@ let rotation_degrees = (self.rotation * 180.0 / PI) as i32;
         );
     }
 
-
-
-
-    pub fn set_position(&mut self, x: f64, y: f64) {
-        self.x = x;
-        self.y = y;
+    // stand on the ground of `body` at world angle `angle` (as of now), moving with it
+    pub fn place_on_surface(&mut self, system: &System, body: usize, angle: f64) {
+        let longitude = angle - system.bodies[body].spin * system.time;
+        self.status = FlightStatus::Landed { body, longitude };
+        self.primary = body;
+        self.follow_surface(system, body, longitude);
     }
 
-    pub fn set_velocity(&mut self, x: f64, y: f64) {
-        self.velocity_x = x;
-        self.velocity_y = y;
-        }
+    // position and velocity of the spot at `longitude` on the spinning body, pointing up
+    fn follow_surface(&mut self, system: &System, body: usize, longitude: f64) {
+        let ground = system.bodies[body];
+        let up = longitude + ground.spin * system.time;
+        let offset = Vec2::from_angle(up) * ground.radius;
+        self.body.position = ground.position + offset;
+        self.body.velocity = ground.surface_velocity(offset);
+        self.rotation = up;
+    }
 
-    pub fn update_position(&mut self) {
-        self.x += self.velocity_x;
-        self.y += self.velocity_y;
+    // the engine is lit, set above zero and has a stage left to fire
+    pub fn is_firing(&self) -> bool {
+        self.engine_on && self.throttle > 0.0 && self.propulsion.active().is_some()
     }
 
+    pub fn toggle_engine(&mut self) {
+        self.engine_on = !self.engine_on;
+    }
 
-pub fn apply_gravity(&mut self, planet:Planet) {
+    pub fn throttle_by(&mut self, change: f64) {
+        self.throttle = (self.throttle + change).clamp(0.0, 1.0);
+    }
 
-    
-    let dx = planet.x - self.x;
-    let dy = planet.y - self.y;
-    let distance = (dx * dx + dy * dy).sqrt();
+    // turning by hand drops any hold
+    pub fn rotate(&mut self, angle: f64) {
+        self.attitude = Attitude::Manual;
+        self.rotation = kepler::normalize_angle(self.rotation + angle);
+    }
 
-    // Avoid division by zero
-    if distance == 0.0 {
-        return;
+    // point the nose as the hold asks, relative to the primary. a hold without a
+    // direction (standing still) keeps the current rotation
+    pub fn steer(&mut self, system: &System) {
+        if self.status != FlightStatus::Flying {
+            return;
+        }
+        let primary = system.bodies[self.primary];
+        let velocity = self.body.velocity - primary.velocity;
+        let radial = self.body.position - primary.position;
+        let direction = match self.attitude {
+            Attitude::Manual => return,
+            Attitude::Prograde => velocity,
+            Attitude::Retrograde => -velocity,
+            Attitude::RadialOut => radial,
+            Attitude::RadialIn => -radial,
+        };
+        if direction.length_squared() > 0.0 {
+            self.rotation = direction.angle();
+        }
     }
 
-    // Normalize the direction vector
-    let direction = (dx / distance, dy / distance);
-    // Apply gravitational force with a stronger effect
-    let gravity_effect = planet.gravity / (distance * distance); // Inverse-square law for gravity
-    log!("gravity {}",direction.1);
-    log!("gravity {}",gravity_effect);
+    // run the engine for the coming frame of `delta_time` seconds: burns propellant and
+    // returns the thrust acceleration to hold over the frame (which gives exactly the
+    // burn's delta-v) with the burn itself. lifts the rocket off the body it sits on.
+    pub fn ignite(&mut self, delta_time: f64) -> (Vec2, Burn) {
+        if !self.is_firing() || self.status.is_wrecked() {
+            return (Vec2::ZERO, Burn::default());
+        }
 
-    self.velocity_x += direction.0 * gravity_effect;
-    self.velocity_y += direction.1 * gravity_effect;
-}
+        let burn = self.propulsion.burn(self.throttle, delta_time);
+        self.body.mass = self.propulsion.mass();
+        if burn.delta_v <= 0.0 {
+            return (Vec2::ZERO, burn);
+        }
+        if let FlightStatus::Landed { .. } = self.status {
+            self.status = FlightStatus::Flying;
+        }
+        (Vec2::from_angle(self.rotation) * (burn.delta_v / delta_time), burn)
+    }
 
+    // inside the primary's atmosphere and flying, or lifting off with the engine
+    // firing; sitting on the pad with the engine off does not count
+    pub fn in_atmosphere(&self, system: &System) -> bool {
+        let moving = self.status == FlightStatus::Flying || (self.is_firing() && !self.status.is_wrecked());
+        moving && system.bodies[self.primary].air_at(self.body.position).is_some()
+    }
 
+    // drag acceleration to hold over the coming `delta_time` seconds next to `thrust`,
+    // with dynamic pressure and load tracked for the readout. going past the airframe's
+    // limits breaks the rocket up.
+    pub fn aerodynamics(&mut self, system: &System, thrust: Vec2, delta_time: f64) -> Result<Vec2, Failure> {
+        if self.status != FlightStatus::Flying {
+            self.ascent.dynamic_pressure = 0.0;
+            self.ascent.load = 0.0;
+            self.ascent.heat_flux = 0.0;
+            self.ascent.airspeed = 0.0;
+            return Ok(Vec2::ZERO);
+        }
+        let primary = system.bodies[self.primary];
+        self.ascent.altitude = (self.body.position - primary.position).length() - primary.radius;
+
+        let mut drag = Vec2::ZERO;
+        self.ascent.dynamic_pressure = 0.0;
+        self.ascent.heat_flux = 0.0;
+        self.ascent.airspeed = 0.0;
+        if let Some((density, wind)) = primary.air_at(self.body.position) {
+            let air_velocity = self.body.velocity - wind;
+            self.ascent.airspeed = air_velocity.length();
+            let airframe = self.airframe;
+            drag = drag_acceleration(density, air_velocity, airframe.drag_coefficient, airframe.area, self.body.mass);
+            // drag can stop the rocket relative to the air, never push it backwards
+            let limit = air_velocity.length() / delta_time;
+            if drag.length() > limit {
+                drag = drag.normalized() * limit;
+            }
+            self.ascent.dynamic_pressure = dynamic_pressure(density, air_velocity.length());
+            let heating = primary.atmosphere.map_or(0.0, |air| air.heating);
+            self.ascent.heat_flux = heat_flux(density, air_velocity.length(), airframe.nose_radius, heating);
+            self.ascent.heat_load += self.ascent.heat_flux * delta_time;
+            self.ascent.peak_heat_flux = self.ascent.peak_heat_flux.max(self.ascent.heat_flux);
+        }
+        self.ascent.load = (thrust + drag).length() / STANDARD_GRAVITY;
+        if self.ascent.dynamic_pressure > self.ascent.max_q {
+            self.ascent.max_q = self.ascent.dynamic_pressure;
+            self.ascent.max_q_altitude = self.ascent.altitude;
+        }
 
-    pub fn update(&mut self, planets: &[Planet], delta_time: f64) {
-        if self.is_launching {
-            self.launch_power += LAUNCH_POWER_INCREASE;
-            self.launch_power = self.launch_power.min(MAX_LAUNCH_POWER);
-            
-            let launch_angle = std::f64::consts::PI / 4.0; // 45 degrees
-            let acceleration = self.launch_power / self.mass; // Multiply by 1000 for more noticeable effect
-            self.velocity_x += acceleration * launch_angle.cos() * delta_time;
-            self.velocity_y += acceleration * launch_angle.sin() * delta_time;
-
-            
-            let sun = planets.iter().find(|p| p.sun).unwrap();
-            //let earth = planets.iter().find(|p| p.sun).unwrap();
-            self.apply_gravity(sun.clone());
-
-            let dx = sun.x - self.x;
-            let dy = sun.y - self.y;
-            let distance = (dx * dx + dy * dy).sqrt();
-            let force = G * sun.mass / (distance * distance);
-            let angle = dy.atan2(dx);
-            
-            //self.velocity_x += force * angle.cos() * delta_time / 1_000_000.0;;
-            //self.velocity_y += force * angle.sin() * delta_time / 1_000_000.0;;
-    
-    
-    
-    
-            //self.x += self.velocity_x * delta_time;
-            self.y -= self.velocity_y * delta_time * 1000.0;
-            log!("CALC: ({}, {}, {})", self.velocity_y, delta_time, self.y);
-
-    
-            self.x = self.x.mul_add(SCALE, WIDTH as f64 / 2.0);
-            self.y = self.y.mul_add(SCALE, HEIGHT as f64 / 2.0);
-    
-    
-            self.rotation = self.velocity_y.atan2(self.velocity_x);
-        }
-
-    
-
-        log!("Position: ({}, {})", self.x, self.y);
-        log!("Velocity: ({}, {})", self.velocity_x, self.velocity_y);
-       
-    }
- 
-}
+        let failure = if self.ascent.dynamic_pressure > self.airframe.max_q {
+            Some(Failure::DynamicPressure(self.ascent.dynamic_pressure))
+        } else if self.ascent.load > self.airframe.max_load {
+            Some(Failure::Overload(self.ascent.load))
+        } else if self.ascent.heat_flux > self.airframe.max_heat_flux {
+            Some(Failure::Overheat(self.ascent.heat_flux))
+        } else if self.ascent.heat_load > self.airframe.max_heat_load {
+            Some(Failure::HeatLoad(self.ascent.heat_load))
+        } else if self.ascent.airspeed > self.airframe.max_speed {
+            Some(Failure::Overspeed(self.ascent.airspeed))
+        } else {
+            None
+        };
+        match failure {
+            Some(failure) => {
+                self.status = FlightStatus::BrokenUp;
+                Err(failure)
+            }
+            None => Ok(drag),
+        }
+    }
 
-#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
-struct RocketData {
-    rockets: Vec<Rocket>,
-}
+    // where the rocket will be relative to its primary after `delta_time` seconds on a
+    // kepler orbit, or `None` when it has to be integrated with the planets instead
+    // (while burning or in the air)
+    pub fn coast(&self, system: &System, delta_time: f64) -> Option<Body> {
+        if self.status != FlightStatus::Flying
+            || self.is_firing()
+            || self.propagation != Propagation::PatchedConic
+            || self.in_atmosphere(system)
+        {
+            return None;
+        }
+        // work in the primary's frame: whatever pulls on the primary pulls on the rocket
+        // about as much, which is what makes the sphere of influence a good split
+        let primary = system.bodies[self.primary];
+        let craft = Body {
+            position: self.body.position - primary.position,
+            velocity: self.body.velocity - primary.velocity,
+            ..self.body
+        };
+        let center = Body {
+            position: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            ..primary
+        };
+        coast(&craft, &center, delta_time)
+    }
 
-fn load_rockets_from_json(file_path: &str) -> Result<HashMap<String, Rocket>> {
-    let mut file = File::open(file_path).expect("Failed to open file");
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).expect("Failed to read file");
+    // bring the rocket up to date once the system has advanced: follow the body it sits
+    // on or apply the conic from `coast`. returns the new primary when the rocket crossed
+    // into another sphere of influence (`root` is the body that owns everything else).
+    pub fn update(&mut self, system: &System, coasted: Option<Body>, root: usize) -> Option<usize> {
+        match self.status {
+            FlightStatus::Crashed { .. } | FlightStatus::BrokenUp => return None,
+            FlightStatus::Landed { body, longitude } => {
+                self.follow_surface(system, body, longitude);
+                return None;
+            }
+            FlightStatus::Flying => {}
+        }
 
-    let rocket_data: RocketData = serde_json::from_str(&contents)?;
-    
-    let mut rockets = HashMap::new();
-    for rocket in rocket_data.rockets {
-        rockets.insert(rocket.name.clone(), rocket);
+        if let Some(relative) = coasted {
+            let primary = system.bodies[self.primary];
+            self.body.position = primary.position + relative.position;
+            self.body.velocity = primary.velocity + relative.velocity;
+        }
+        let primary = system.primary_at(self.body.position, root);
+        if primary == self.primary {
+            return None;
+        }
+        self.primary = primary;
+        Some(primary)
     }
-
-    Ok(rockets)
 }
-
-pub fn list_rockets() -> Result<()> {
-    let rockets = load_rockets_from_json("./data/rockets.json")?;
-    Ok(())
-}
\ No newline at end of file
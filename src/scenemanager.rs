@@ -7,4 +7,5 @@ pub enum Screen {
     Title,
     Game,
     RocketSelect,
+    Porkchop,
 }
\ No newline at end of file
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+use std::f64::consts::PI;
+use std::fmt;
+
+use serde::Deserialize;
+use turbo::prelude::*;
+
+use crate::json;
+use crate::physics::ephemeris::place_at_epoch;
+use crate::physics::{Atmosphere, Body, System, Vec2, AU};
+use crate::planet::Planet;
+
+// read from disk when running natively so bodies can be tweaked without a rebuild,
+// the copy compiled into the game is used everywhere else (the wasm build has no files)
+const PLANETS_PATH: &str = "src/data/planets.json";
+const EMBEDDED_PLANETS: &str = include_str!("data/planets.json");
+
+#[derive(Debug, Deserialize)]
+struct SolarSystemData {
+    bodies: Vec<serde_json::Value>, // read one by one, see `parse_body`
+}
+
+#[derive(Debug, Deserialize)]
+struct BodyData {
+    name: String,
+    #[serde(default)]
+    sun: bool,
+    mass_kg: f64,
+    radius_km: f64,        // physical radius
+    display_radius: f64,   // diameter of the circle drawn on screen, in pixels
+    color: String,         // hex, e.g. "0x006495ED"
+    #[serde(default)]
+    sprite: Option<String>,
+    position_au: [f64; 2],
+    velocity_km_s: [f64; 2],
+    #[serde(default)]
+    trail_length: Option<usize>, // points kept in the orbit trail
+    #[serde(default)]
+    rotation_period_h: Option<f64>, // sidereal day, negative for retrograde rotation
+    #[serde(default)]
+    atmosphere: Option<AtmosphereData>,
+}
+
+// exponential atmosphere, see `physics::Atmosphere`
+#[derive(Debug, Deserialize)]
+struct AtmosphereData {
+    surface_density_kg_m3: f64,
+    scale_height_km: f64,
+    height_km: f64, // top of the modelled atmosphere above the surface
+    #[serde(default = "default_heating_coefficient")]
+    heating_coefficient: f64, // sutton-graves constant, kg^0.5 / m
+}
+
+// sutton-graves constant of earth's air, used for atmospheres that do not give their own
+const EARTH_AIR_HEATING: f64 = 1.7415e-4;
+
+fn default_heating_coefficient() -> f64 {
+    EARTH_AIR_HEATING
+}
+
+#[derive(Debug)]
+pub enum SolarSystemError {
+    Json(serde_json::Error),
+    // a single entry is unusable; index is its position in the `bodies` array and
+    // field the path inside it, e.g. `atmosphere.height_km`
+    InvalidBody {
+        index: usize,
+        name: String,
+        field: String,
+        reason: String,
+    },
+    NoBodies,
+    NoSun,
+}
+
+impl fmt::Display for SolarSystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "malformed planets.json: {e}"),
+            Self::InvalidBody {
+                index,
+                name,
+                field,
+                reason,
+            } => write!(f, "body #{index} ({name:?}): `{field}` {reason}"),
+            Self::NoBodies => write!(f, "planets.json does not define any bodies"),
+            Self::NoSun => write!(f, "planets.json must mark exactly one body with \"sun\": true"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SolarSystemError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+// load the solar system, preferring the file on disk and falling back to the embedded copy
+pub fn load_solar_system() -> (System, Vec<Planet>) {
+    if let Ok(contents) = std::fs::read_to_string(PLANETS_PATH) {
+        match parse_solar_system(&contents) {
+            Ok(loaded) => return loaded,
+            Err(e) => log!("ignoring {PLANETS_PATH}: {e}"),
+        }
+    }
+    parse_solar_system(EMBEDDED_PLANETS).expect("embedded planets.json is invalid")
+}
+
+// load the solar system and move every planet in the bundled element table to where it
+// was on julian date `jd`; bodies without elements keep the state from planets.json
+pub fn load_solar_system_at(jd: f64) -> (System, Vec<Planet>) {
+    let (mut system, planets) = load_solar_system();
+    let names: Vec<&str> = planets.iter().map(|p| p.name.as_str()).collect();
+    let sun = planets.iter().position(|p| p.sun).unwrap_or(0);
+    place_at_epoch(&mut system, &names, sun, jd);
+    (system, planets)
+}
+
+// entry `index` of `bodies`, naming it and the offending field when it has the wrong shape
+fn parse_body(index: usize, value: serde_json::Value) -> Result<BodyData, SolarSystemError> {
+    let name = value.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string();
+    json::parse_entry(value).map_err(|(field, reason)| SolarSystemError::InvalidBody {
+        index,
+        name,
+        field,
+        reason,
+    })
+}
+
+// planets[i] draws system.bodies[i]
+pub fn parse_solar_system(contents: &str) -> Result<(System, Vec<Planet>), SolarSystemError> {
+    let data: SolarSystemData = serde_json::from_str(contents)?;
+    if data.bodies.is_empty() {
+        return Err(SolarSystemError::NoBodies);
+    }
+    let entries = data
+        .bodies
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| parse_body(index, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    if entries.iter().filter(|b| b.sun).count() != 1 {
+        return Err(SolarSystemError::NoSun);
+    }
+
+    let mut names = HashSet::new();
+    let mut bodies = Vec::with_capacity(entries.len());
+    let mut planets = Vec::with_capacity(entries.len());
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let invalid = |field: &str, reason: &str| SolarSystemError::InvalidBody {
+            index,
+            name: entry.name.clone(),
+            field: field.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if entry.name.trim().is_empty() {
+            return Err(invalid("name", "must not be empty"));
+        }
+        if !names.insert(entry.name.clone()) {
+            return Err(invalid("name", "is used by more than one body"));
+        }
+        if !(entry.mass_kg.is_finite() && entry.mass_kg > 0.0) {
+            return Err(invalid("mass_kg", "must be a positive number"));
+        }
+        if !(entry.radius_km.is_finite() && entry.radius_km > 0.0) {
+            return Err(invalid("radius_km", "must be a positive number"));
+        }
+        if !(entry.display_radius.is_finite() && entry.display_radius > 0.0) {
+            return Err(invalid("display_radius", "must be a positive number"));
+        }
+        if !entry.position_au.iter().all(|v| v.is_finite()) {
+            return Err(invalid("position_au", "must contain two finite numbers"));
+        }
+        if !entry.velocity_km_s.iter().all(|v| v.is_finite()) {
+            return Err(invalid("velocity_km_s", "must contain two finite numbers"));
+        }
+        if entry.rotation_period_h.is_some_and(|h| !h.is_finite() || h == 0.0) {
+            return Err(invalid("rotation_period_h", "must be a non-zero number of hours"));
+        }
+        if let Some(air) = &entry.atmosphere {
+            if !(air.surface_density_kg_m3.is_finite() && air.surface_density_kg_m3 > 0.0) {
+                return Err(invalid("atmosphere.surface_density_kg_m3", "must be a positive number"));
+            }
+            if !(air.scale_height_km.is_finite() && air.scale_height_km > 0.0) {
+                return Err(invalid("atmosphere.scale_height_km", "must be a positive number"));
+            }
+            if !(air.height_km.is_finite() && air.height_km > 0.0) {
+                return Err(invalid("atmosphere.height_km", "must be a positive number"));
+            }
+            if !(air.heating_coefficient.is_finite() && air.heating_coefficient > 0.0) {
+                return Err(invalid("atmosphere.heating_coefficient", "must be a positive number"));
+            }
+        }
+        let color = parse_color(&entry.color)
+            .ok_or_else(|| invalid("color", "must be a hex color such as \"0x006495ED\""))?;
+
+        let mut body = Body::new(
+            Vec2::new(entry.position_au[0] * AU, entry.position_au[1] * AU),
+            Vec2::new(entry.velocity_km_s[0] * 1000.0, entry.velocity_km_s[1] * 1000.0),
+            entry.mass_kg,
+        );
+        body.radius = entry.radius_km * 1000.0;
+        if let Some(hours) = entry.rotation_period_h {
+            body.spin = 2.0 * PI / (hours * 3600.0);
+        }
+        body.atmosphere = entry.atmosphere.map(|air| {
+            Atmosphere::new(
+                air.surface_density_kg_m3,
+                air.scale_height_km * 1000.0,
+                air.height_km * 1000.0,
+                air.heating_coefficient,
+            )
+        });
+
+        let mut planet = Planet::new(&entry.name, entry.display_radius, color);
+        planet.sun = entry.sun;
+        if let Some(sprite) = entry.sprite {
+            planet.set_sprite(&sprite);
+        }
+        if let Some(trail_length) = entry.trail_length {
+            planet.trail_length = trail_length;
+        }
+
+        bodies.push(body);
+        planets.push(planet);
+    }
+
+    Ok((System::new(bodies), planets))
+}
+
+// "0x006495ED", "#6495ED" or "6495ED"
+fn parse_color(text: &str) -> Option<u32> {
+    let digits = text
+        .trim()
+        .trim_start_matches("0x")
+        .trim_start_matches("0X")
+        .trim_start_matches('#')
+        .replace('_', "");
+    if digits.is_empty() || digits.len() > 8 {
+        return None;
+    }
+    u32::from_str_radix(&digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the embedded planets.json with `change` applied to body number `index`
+    fn planets_with(index: usize, change: impl FnOnce(&mut serde_json::Value)) -> String {
+        let mut planets: serde_json::Value = serde_json::from_str(EMBEDDED_PLANETS).unwrap();
+        change(&mut planets["bodies"][index]);
+        planets.to_string()
+    }
+
+    fn invalid_body(contents: &str) -> (usize, String, String) {
+        match parse_solar_system(contents) {
+            Err(SolarSystemError::InvalidBody { index, name, field, .. }) => (index, name, field),
+            Err(e) => panic!("expected an invalid body, got {e}"),
+            Ok(_) => panic!("expected an invalid body, got a solar system"),
+        }
+    }
+
+    #[test]
+    fn embedded_planets_load() {
+        let (system, planets) = parse_solar_system(EMBEDDED_PLANETS).unwrap();
+        assert_eq!(system.bodies.len(), planets.len());
+        assert_eq!(planets.iter().filter(|p| p.sun).count(), 1);
+    }
+
+    #[test]
+    fn malformed_entries_name_the_body_and_field() {
+        let wrong_type = planets_with(1, |earth| earth["mass_kg"] = "heavy".into());
+        assert_eq!(invalid_body(&wrong_type), (1, "Earth".to_string(), "mass_kg".to_string()));
+
+        let missing = planets_with(1, |earth| {
+            earth["atmosphere"].as_object_mut().unwrap().remove("height_km");
+        });
+        assert_eq!(
+            invalid_body(&missing),
+            (1, "Earth".to_string(), "atmosphere.height_km".to_string())
+        );
+
+        let negative = planets_with(1, |earth| earth["radius_km"] = (-6371.0).into());
+        assert_eq!(invalid_body(&negative), (1, "Earth".to_string(), "radius_km".to_string()));
+    }
+}
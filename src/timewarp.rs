@@ -0,0 +1,167 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::physics::{Body, OrbitalElements, System, G};
+
+// simulated seconds per frame for each warp level, and how the level is shown on screen
+pub const LEVELS: [(f64, &str); 5] = [
+    (1.0 / 60.0, "real time"),
+    (60.0, "1 min"),
+    (3600.0, "1 h"),
+    (3600.0 * 24.0, "1 day"),
+    (3600.0 * 24.0 * 7.0, "1 week"),
+];
+
+// warp level the game starts at (1 day per frame, the old fixed timestep)
+const DEFAULT_LEVEL: usize = 3;
+
+// longest a frame may last while the engine is burning
+pub const MAX_BURN_SECONDS: f64 = 60.0;
+
+// and while flying through air, which is then crossed in one second substeps
+pub const MAX_AIR_SECONDS: f64 = 60.0;
+
+// fraction of the distance to the nearest surface the rocket may cover in one frame
+const ENCOUNTER_FRACTION: f64 = 0.1;
+
+#[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct TimeWarp {
+    level: usize,         // index into LEVELS picked by the player
+    pub paused: bool,
+    step_requested: bool, // advance a single frame while paused
+    applied: usize,       // level actually used on the last frame, after limiting
+}
+
+impl TimeWarp {
+    pub fn new() -> Self {
+        Self {
+            level: DEFAULT_LEVEL,
+            paused: false,
+            step_requested: false,
+            applied: DEFAULT_LEVEL,
+        }
+    }
+
+    pub fn faster(&mut self) {
+        self.level = (self.level + 1).min(LEVELS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.level = self.level.saturating_sub(1);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    // only meaningful while paused; the next frame advances by one warp step
+    pub fn request_step(&mut self) {
+        if self.paused {
+            self.step_requested = true;
+        }
+    }
+
+    // simulated seconds to advance this frame, never more than `max_seconds`
+    pub fn frame_seconds(&mut self, max_seconds: f64) -> f64 {
+        // the slowest level is always allowed, otherwise nothing could ever move
+        self.applied = (0..=self.level)
+            .rev()
+            .find(|&i| LEVELS[i].0 <= max_seconds)
+            .unwrap_or(0);
+
+        if self.paused && !std::mem::take(&mut self.step_requested) {
+            return 0.0;
+        }
+        LEVELS[self.applied].0
+    }
+
+    // true when the last frame ran slower than the level the player picked
+    pub fn is_limited(&self) -> bool {
+        self.applied < self.level
+    }
+
+    pub fn label(&self) -> String {
+        if self.paused {
+            return format!("PAUSED ({}/frame)", LEVELS[self.level].1);
+        }
+        if self.is_limited() {
+            format!("{}/frame (limited from {})", LEVELS[self.applied].1, LEVELS[self.level].1)
+        } else {
+            format!("{}/frame", LEVELS[self.applied].1)
+        }
+    }
+}
+
+// longest frame that keeps `craft` from covering more than a fraction of its distance
+// to any body's surface (or air) in one go. a craft on a closed orbit that stays clear of
+// a body is never limited by it, and one on an open path only by how fast it closes in,
+// so parked orbits and cruise can be warped through at full speed.
+pub fn encounter_limit(system: &System, craft: &Body) -> f64 {
+    system
+        .bodies
+        .iter()
+        .map(|body| {
+            let position = craft.position - body.position;
+            let velocity = craft.velocity - body.velocity;
+            let center = position.length();
+            let air = body.atmosphere.map_or(0.0, |air| air.height);
+            let clear = body.radius + air;
+            let orbit = OrbitalElements::from_state(position, velocity, G * body.mass);
+            if orbit.is_bound() && orbit.periapsis > clear {
+                return f64::INFINITY;
+            }
+            let distance = if center > clear { center - clear } else { center };
+            // a closed orbit that dips into the air or the ground comes back down soon
+            // whichever way it is moving now; an open one only while it is falling in
+            let speed = if orbit.is_bound() {
+                velocity.length()
+            } else {
+                -position.dot(velocity) / center
+            };
+            if speed > 0.0 {
+                ENCOUNTER_FRACTION * distance / speed
+            } else {
+                f64::INFINITY
+            }
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Atmosphere, Vec2};
+
+    fn earth() -> Body {
+        let mut earth = Body::new(Vec2::ZERO, Vec2::ZERO, 5.9742e24);
+        earth.radius = 6.371e6;
+        earth.atmosphere = Some(Atmosphere::new(1.225, 8.5e3, 140.0e3, 1.7415e-4));
+        earth
+    }
+
+    // parked above the air: nothing to slow down for
+    #[test]
+    fn parking_orbit_is_not_limited() {
+        let earth = earth();
+        let radius = earth.radius + 200.0e3;
+        let speed = (G * earth.mass / radius).sqrt();
+        let craft = Body::new(Vec2::new(radius, 0.0), Vec2::new(0.0, speed), 0.0);
+        let system = System::new(vec![earth]);
+        assert_eq!(encounter_limit(&system, &craft), f64::INFINITY);
+    }
+
+    // an orbit grazing the air, or a hyperbola falling in, is
+    #[test]
+    fn falling_in_is_limited() {
+        let earth = earth();
+        let radius = earth.radius + 200.0e3;
+        let speed = 0.99 * (G * earth.mass / radius).sqrt();
+        let grazing = Body::new(Vec2::new(radius, 0.0), Vec2::new(0.0, speed), 0.0);
+        let falling = Body::new(Vec2::new(1.0e9, 0.0), Vec2::new(-3000.0, 100.0), 0.0);
+        let leaving = Body::new(Vec2::new(1.0e9, 0.0), Vec2::new(3000.0, 100.0), 0.0);
+        let system = System::new(vec![earth]);
+        assert!(encounter_limit(&system, &grazing) < 10.0);
+        let expected = ENCOUNTER_FRACTION * (1.0e9 - earth.radius - 140.0e3) / 3000.0;
+        assert!((encounter_limit(&system, &falling) - expected).abs() < 1.0e-6 * expected);
+        assert_eq!(encounter_limit(&system, &leaving), f64::INFINITY);
+    }
+}
@@ -0,0 +1,72 @@
+// orbit trails: a bounded ring buffer per body, decimated by distance.
+// trails live outside `GameState` so they are never serialized by `state.save()`;
+// losing them (e.g. on a hot reload) only costs a moment of history.
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+use crate::physics::Vec2;
+
+// default number of points a trail keeps
+pub const TRAIL_LENGTH: usize = 600;
+
+#[derive(Clone, Debug, Default)]
+pub struct Trail {
+    points: VecDeque<Vec2>,
+    capacity: usize,
+    min_spacing: f64, // world meters between kept points
+}
+
+impl Trail {
+    pub fn new(capacity: usize, min_spacing: f64) -> Self {
+        Self {
+            points: VecDeque::with_capacity(capacity),
+            capacity,
+            min_spacing,
+        }
+    }
+
+    // keep `point` if it is far enough from the last kept point, dropping the oldest
+    // point once the trail is full
+    pub fn push(&mut self, point: Vec2) {
+        if let Some(&last) = self.points.back() {
+            if (point - last).length() < self.min_spacing {
+                return;
+            }
+        }
+        if self.points.len() == self.capacity {
+            self.points.pop_front();
+        }
+        if self.capacity > 0 {
+            self.points.push_back(point);
+        }
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Vec2> {
+        self.points.iter()
+    }
+}
+
+thread_local! {
+    static TRAILS: RefCell<HashMap<String, Trail>> = RefCell::new(HashMap::new());
+}
+
+// add a point to the trail called `name`, creating it with the given settings if needed
+pub fn record(name: &str, point: Vec2, capacity: usize, min_spacing: f64) {
+    TRAILS.with(|trails| {
+        trails
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| Trail::new(capacity, min_spacing))
+            .push(point);
+    });
+}
+
+// run `f` on the trail called `name`, if there is one
+pub fn with_trail<R>(name: &str, f: impl FnOnce(&Trail) -> R) -> Option<R> {
+    TRAILS.with(|trails| trails.borrow().get(name).map(f))
+}
+
+// forget every trail, e.g. when a new run starts
+pub fn clear_all() {
+    TRAILS.with(|trails| trails.borrow_mut().clear());
+}
@@ -0,0 +1,171 @@
+// predicted rocket path: propagated on a copy of the physics state and cached between
+// frames (outside the saved state) so it is only recomputed when something changes
+use std::cell::RefCell;
+
+use turbo::prelude::*;
+
+use crate::hud::{format_distance, format_duration};
+use crate::camera::Camera;
+use crate::physics::{coast, predict, Body, Maneuver, Marker, Prediction, PredictionRequest, System, Vec2};
+
+// default look-ahead, seconds
+pub const PREDICTION_HORIZON: f64 = 3600.0 * 24.0 * 365.0;
+
+// never look further ahead than this many orbits around the primary
+const MAX_ORBITS: f64 = 3.0;
+
+const SAMPLES: usize = 400;
+
+// also propagate and draw where the planets are going
+const PREDICT_PLANETS: bool = false;
+
+// recompute at least this often even if nothing changed, in frames
+const REFRESH_FRAMES: u32 = 30;
+
+const PATH_COLOR: u32 = 0x88FF_88AA;
+// the path after the first planned maneuver
+const PLANNED_COLOR: u32 = 0xFFAA_44AA;
+
+// what the prediction depends on besides the physics state itself
+#[derive(Clone, Debug, PartialEq)]
+pub struct PredictionKey {
+    pub thrusting: bool,
+    pub warp: String,
+    pub primary: usize,
+    pub target: Option<usize>,
+    pub maneuvers: Vec<Maneuver>,
+}
+
+struct Cached {
+    key: PredictionKey,
+    age: u32, // frames since the prediction was computed
+    prediction: Prediction,
+}
+
+thread_local! {
+    static CACHE: RefCell<Option<Cached>> = const { RefCell::new(None) };
+}
+
+// refresh the cached prediction if the inputs changed or it is getting old
+pub fn update(system: &System, craft: &Body, key: PredictionKey, horizon: f64) {
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let stale = match cache.as_mut() {
+            Some(cached) => {
+                cached.age += 1;
+                cached.key != key || cached.age >= REFRESH_FRAMES
+            }
+            None => true,
+        };
+        if !stale {
+            return;
+        }
+
+        // an orbit repeats itself, so there is no point propagating it for a year. with
+        // maneuvers planned it is the orbit after the last one that counts
+        let (start, orbit) = match after_maneuvers(system, craft, key.primary, &key.maneuvers) {
+            Some((start, craft)) => (start, system.elements(&craft, key.primary)),
+            None => (0.0, system.elements(craft, key.primary)),
+        };
+        let horizon = if orbit.is_bound() {
+            horizon.min(start + MAX_ORBITS * orbit.period)
+        } else {
+            horizon
+        };
+        let request = PredictionRequest {
+            horizon,
+            samples: SAMPLES,
+            primary: key.primary,
+            target: key.target,
+            include_bodies: PREDICT_PLANETS,
+            maneuvers: key.maneuvers.clone(),
+        };
+        *cache = Some(Cached {
+            prediction: predict(system, craft, &request),
+            key,
+            age: 0,
+        });
+    });
+}
+
+// seconds until the last maneuver and the craft right after it, on kepler orbits around
+// `primary` (a quick estimate, the prediction itself integrates properly)
+fn after_maneuvers(system: &System, craft: &Body, primary: usize, maneuvers: &[Maneuver]) -> Option<(f64, Body)> {
+    let mut sorted = maneuvers.to_vec();
+    sorted.sort_by(|a, b| a.time.total_cmp(&b.time));
+    let center = system.bodies[primary];
+    let mut state = *craft;
+    let mut time = system.time;
+    for maneuver in sorted.iter().filter(|m| m.time >= system.time) {
+        state = coast(&state, &center, maneuver.time - time)?;
+        state.velocity += maneuver.velocity_change(state.position - center.position, state.velocity - center.velocity);
+        time = maneuver.time;
+    }
+    (time > system.time).then_some((time - system.time, state))
+}
+
+// run `f` on the current prediction, if there is one
+pub fn with_prediction<R>(f: impl FnOnce(&Prediction) -> R) -> Option<R> {
+    CACHE.with(|cache| cache.borrow().as_ref().map(|cached| f(&cached.prediction)))
+}
+
+// forget the prediction, e.g. when a new run starts
+pub fn clear() {
+    CACHE.with(|cache| *cache.borrow_mut() = None);
+}
+
+// draw the predicted path with its apsides and the closest approach to the target.
+// the path is drawn in the primary's frame, anchored at `primary_now`, so an orbit
+// around a moving planet shows up as a closed curve instead of a smeared spiral
+pub fn draw(camera: &Camera, primary_now: Vec2, primary_name: &str, target_name: &str) {
+    CACHE.with(|cache| {
+        let cache = cache.borrow();
+        let Some(cached) = cache.as_ref() else {
+            return;
+        };
+        let prediction = &cached.prediction;
+
+        let to_screen = |p: Vec2, primary: Vec2| camera.to_screen(primary_now + (p - primary));
+        let paths = std::iter::once(&prediction.points).chain(&prediction.body_paths);
+        for (n, path) in paths.enumerate() {
+            let points: Vec<(i32, i32)> = path
+                .iter()
+                .zip(&prediction.primary_path)
+                .map(|(&p, &primary)| to_screen(p, primary))
+                .collect();
+            // only the rocket's own path changes at a maneuver
+            let burned = if n == 0 { prediction.first_burned } else { None };
+            for (i, window) in points.windows(2).enumerate() {
+                let color = match burned {
+                    Some(first) if i + 1 >= first => PLANNED_COLOR,
+                    _ => PATH_COLOR,
+                };
+                path!(start = window[0], end = window[1], color = color, width = 1);
+            }
+        }
+
+        let label = |marker: Marker, text: String| {
+            let (x, y) = to_screen(marker.position, marker.primary_position);
+            circ!(d = 6, x = x - 3, y = y - 3, color = PATH_COLOR);
+            text!(&text, x = x + 6, y = y - 4, font = Font::S);
+        };
+        if let Some(pe) = prediction.periapsis {
+            label(
+                pe,
+                format!("Pe {} from {primary_name} in {}", format_distance(pe.distance), format_duration(pe.time)),
+            );
+        }
+        if let Some(ap) = prediction.apoapsis {
+            label(
+                ap,
+                format!("Ap {} in {}", format_distance(ap.distance), format_duration(ap.time)),
+            );
+        }
+        if let Some(ca) = prediction.closest_approach {
+            label(
+                ca,
+                format!("closest to {target_name}: {} in {}", format_distance(ca.distance), format_duration(ca.time)),
+            );
+        }
+    });
+}